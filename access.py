@@ -0,0 +1,112 @@
+
+from attrs import define
+
+from typing import Dict, List, Optional
+import re
+
+from filesystem import DirectoryZip, FileZip
+from output import AccessOverlapReport
+
+
+# where forge looks for a mod's access transformer
+FORGE_TRANSFORMER = 'META-INF/accesstransformer.cfg'
+
+TRANSFORMER_MODIFIER = re.compile(
+    r'(public|protected|default|private)([-+]f)?'
+)
+WIDENER_ACCESS = ['accessible', 'extendable', 'mutable']
+
+
+@define
+class Access:
+    # eg: `net.minecraft.client.Minecraft.f_91074_` for a field, with the
+    # descriptor for methods, or the class alone
+    target: str
+    # eg: `public-f` for transformers, `accessible` or `mutable` for wideners
+    access: str
+
+
+def member(owner: str, name: Optional[str], descriptor: str = '') -> str:
+    owner = owner.replace('/', '.')
+    if name is None:
+        return owner
+    return f'{owner}.{name}{descriptor}'
+
+
+# `public-f net.minecraft.Foo f_1234_ # comment`
+def parse_transformer(text: str) -> List[Access]:
+    entries: List[Access] = []
+    for line in text.splitlines():
+        parts = line.split('#', 1)[0].split()
+        if len(parts) < 2 or not TRANSFORMER_MODIFIER.fullmatch(parts[0]):
+            continue
+        name = parts[2] if len(parts) > 2 else None
+        entries.append(Access(member(parts[1], name), parts[0]))
+    return entries
+
+
+# `accessible method net/minecraft/Foo bar (I)V` after an
+# `accessWidener v2 named` header
+def parse_widener(text: str) -> List[Access]:
+    entries: List[Access] = []
+    for line in text.splitlines()[1:]:
+        parts = line.split('#', 1)[0].split()
+        if len(parts) < 3:
+            continue
+        access = parts[0].removeprefix('transitive-')
+        if access not in WIDENER_ACCESS:
+            continue
+        if parts[1] == 'class':
+            entries.append(Access(member(parts[2], None), access))
+        elif parts[1] == 'method' and len(parts) >= 5:
+            entries.append(
+                Access(member(parts[2], parts[3], parts[4]), access)
+            )
+        elif parts[1] == 'field' and len(parts) >= 4:
+            entries.append(Access(member(parts[2], parts[3]), access))
+    return entries
+
+
+def read_access(jar: DirectoryZip, files: List[str]) -> List[Access]:
+    entries: Dict[str, Access] = {}
+    for path in files:
+        if not jar.has(path):
+            continue
+        try:
+            text = FileZip(path, jar).read().decode()
+        except UnicodeDecodeError:
+            continue
+        if text.lstrip().startswith('accessWidener'):
+            parsed = parse_widener(text.lstrip())
+        else:
+            parsed = parse_transformer(text)
+        for entry in parsed:
+            entries.setdefault(entry.target, entry)
+    return list(entries.values())
+
+
+# `+f` makes a member final, while `-f` and `mutable` need it not to be
+def is_incompatible(accesses: List[str]) -> bool:
+    adds = any([x.endswith('+f') for x in accesses])
+    removes = any([x.endswith('-f') or x == 'mutable' for x in accesses])
+    return adds and removes
+
+
+# game members whose access is changed by more than one mod
+def find_access_overlaps(
+            access: Dict[str, List[Access]]
+        ) -> List[AccessOverlapReport]:
+    targets: Dict[str, AccessOverlapReport] = {}
+    for modid in sorted(access.keys()):
+        for entry in access[modid]:
+            report = targets.setdefault(
+                entry.target,
+                AccessOverlapReport(entry.target)
+            )
+            report.access[modid] = entry.access
+
+    overlaps = [x for x in targets.values() if len(x.access) > 1]
+    for overlap in overlaps:
+        overlap.incompatible = is_incompatible(list(overlap.access.values()))
+    overlaps.sort(key=lambda x: (not x.incompatible, x.target))
+    return overlaps
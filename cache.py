@@ -0,0 +1,126 @@
+
+from attrs import define, Factory, asdict
+
+from typing import Any, Dict, List, Optional, Type
+import logging
+import json
+import sys
+import os
+
+from filesystem import DirectoryReal
+from metadata import Dependency, ModInfo, ScanResult, METADATA_FORMATS
+from mixins import Mixin
+from access import Access
+
+
+CACHE_FILE = '.mc-packer-cache.json'
+# bump whenever the layout of `ScanResult` or `ModInfo` changes
+CACHE_VERSION = 12
+
+# the first format of each loader, both forge formats have the same fields
+FORMAT_CLASSES: Dict[str, Type[ModInfo]] = {
+    x.LOADER: x for x in reversed(METADATA_FORMATS)
+}
+
+log = logging.getLogger(__name__)
+
+
+def read_result(data: Dict[str, Any]) -> ScanResult:
+    result = ScanResult(data['file'], errors=list(data['errors']))
+    result.found = bool(data['found'])
+    for mod in data['mods']:
+        fields = dict(mod)
+        fields['dependencies'] = [
+            Dependency(**x) for x in mod['dependencies']
+        ]
+        fields['mixins'] = [Mixin(**x) for x in mod['mixins']]
+        fields['access'] = [Access(**x) for x in mod['access']]
+        result.mods.append(FORMAT_CLASSES[mod['loader']](**fields))
+    return result
+
+
+@define
+class ScanCache:
+    path:       str
+    # keyed by the path of the jar
+    entries:    Dict[str, Dict[str, Any]] = Factory(dict)
+
+    @classmethod
+    def open(cls, directory: DirectoryReal) -> 'ScanCache':
+        cache = cls(os.path.join(directory.full_path, CACHE_FILE))
+
+        # a missing, corrupt or outdated cache just means a full rescan
+        try:
+            with open(cache.path, 'r') as file:
+                data = json.load(file)
+            if data.get('version', None) == CACHE_VERSION:
+                cache.entries = dict(data['entries'])
+            else:
+                log.info(f"'{cache.path}' is outdated, rescanning")
+        except (OSError, ValueError, KeyError, TypeError, AttributeError) as e:
+            log.info(f"not using cache '{cache.path}': {e}")
+            cache.entries = {}
+
+        return cache
+
+    @staticmethod
+    def fingerprint(path: str) -> Optional[Dict[str, Any]]:
+        try:
+            stat = os.stat(path)
+        except OSError:
+            return None
+        return {'size': stat.st_size, 'mtime': stat.st_mtime_ns}
+
+    # the format of jars with several depends on the loader of the profile,
+    # so entries scanned for another loader are misses
+    def get(self, path: str, loader: Optional[str]) -> Optional[ScanResult]:
+        entry = self.entries.get(path, None)
+        fingerprint = self.fingerprint(path)
+        if entry is None or fingerprint is None:
+            return None
+
+        try:
+            if entry['fingerprint'] != fingerprint:
+                log.debug(f"'{path}' changed")
+                return None
+            if entry['loader'] != loader:
+                log.debug(f"'{path}' was scanned for another loader")
+                return None
+            result = read_result(entry['result'])
+        except (KeyError, TypeError, ValueError, AttributeError) as e:
+            log.debug(f"invalid entry for '{path}': {e}")
+            return None
+        log.debug(f"hit for '{path}'")
+        return result
+
+    def put(
+                self,
+                path: str,
+                result: ScanResult,
+                loader: Optional[str]
+            ) -> None:
+        self.entries[path] = {
+            'fingerprint': self.fingerprint(path),
+            'loader': loader,
+            'result': asdict(result),
+        }
+
+    # drops entries of jars that are gone
+    def prune(self, paths: List[str]) -> None:
+        self.entries = {
+            key: value for key, value in self.entries.items()
+            if key in paths
+        }
+
+    def save(self) -> None:
+        try:
+            with open(self.path, 'w') as file:
+                json.dump(
+                    {'version': CACHE_VERSION, 'entries': self.entries},
+                    file
+                )
+        except OSError as e:
+            print(
+                f"failed to write cache '{self.path}': {e}",
+                file=sys.stderr
+            )
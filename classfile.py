@@ -0,0 +1,183 @@
+
+from attrs import define, Factory
+
+from typing import Any, Dict, List, Optional, Tuple
+import struct
+
+
+class ClassFormatError(Exception):
+    ...
+
+
+@define
+class Annotation:
+    # a descriptor, eg: `Lorg/spongepowered/asm/mixin/Mixin;`
+    type:   str
+    values: Dict[str, Any] = Factory(dict)
+
+
+@define
+class Method:
+    name:           str
+    descriptor:     str
+    annotations:    List[Annotation] = Factory(list)
+
+
+@define
+class ClassFile:
+    major_version:  int
+    # internal name, eg: `net/minecraft/client/Minecraft`
+    name:           str
+    annotations:    List[Annotation] = Factory(list)
+    methods:        List[Method] = Factory(list)
+
+
+# only what's needed to read annotations, everything else is skipped
+class Reader:
+    data:       bytes
+    offset:     int
+    # utf8 constants by index
+    utf8:       Dict[int, str]
+    # class constants by index, pointing to their name's utf8 index
+    classes:    Dict[int, int]
+
+    def __init__(self, data: bytes):
+        self.data = data
+        self.offset = 0
+        self.utf8 = {}
+        self.classes = {}
+
+    def unpack(self, format: str) -> Tuple[Any, ...]:
+        size = struct.calcsize(format)
+        if self.offset + size > len(self.data):
+            raise ClassFormatError('unexpected end of class file')
+        values = struct.unpack_from(format, self.data, self.offset)
+        self.offset += size
+        return values
+
+    def u1(self) -> int:
+        return self.unpack('>B')[0]
+
+    def u2(self) -> int:
+        return self.unpack('>H')[0]
+
+    def u4(self) -> int:
+        return self.unpack('>I')[0]
+
+    def skip(self, size: int) -> None:
+        if self.offset + size > len(self.data):
+            raise ClassFormatError('unexpected end of class file')
+        self.offset += size
+
+    def string(self, index: int) -> str:
+        if index not in self.utf8:
+            raise ClassFormatError(f'invalid utf8 constant #{index}')
+        return self.utf8[index]
+
+    def class_name(self, index: int) -> str:
+        if index not in self.classes:
+            raise ClassFormatError(f'invalid class constant #{index}')
+        return self.string(self.classes[index])
+
+    def constant_pool(self) -> None:
+        count = self.u2()
+        index = 1
+        while index < count:
+            tag = self.u1()
+            if tag == 1:
+                length = self.u2()
+                raw = self.data[self.offset:self.offset + length]
+                self.skip(length)
+                # modified utf8 only differs for nulls and surrogates
+                self.utf8[index] = raw.decode('utf-8', errors='replace')
+            elif tag == 7:
+                self.classes[index] = self.u2()
+            elif tag in [8, 16, 19, 20]:
+                self.skip(2)
+            elif tag == 15:
+                self.skip(3)
+            elif tag in [3, 4, 9, 10, 11, 12, 17, 18]:
+                self.skip(4)
+            elif tag in [5, 6]:
+                self.skip(8)
+                # longs and doubles take up two entries
+                index += 1
+            else:
+                raise ClassFormatError(f'unknown constant pool tag {tag}')
+            index += 1
+
+    def element_value(self) -> Any:
+        tag = chr(self.u1())
+        if tag in 'BCDFIJSZ':
+            self.skip(2)
+            return None
+        if tag == 's':
+            return self.string(self.u2())
+        if tag == 'e':
+            self.skip(4)
+            return None
+        if tag == 'c':
+            return self.string(self.u2())
+        if tag == '@':
+            return self.annotation()
+        if tag == '[':
+            return [self.element_value() for _ in range(self.u2())]
+        raise ClassFormatError(f'unknown element value tag {tag!r}')
+
+    def annotation(self) -> Annotation:
+        annotation = Annotation(self.string(self.u2()))
+        for _ in range(self.u2()):
+            name = self.string(self.u2())
+            annotation.values[name] = self.element_value()
+        return annotation
+
+    def attributes(self) -> List[Annotation]:
+        annotations: List[Annotation] = []
+        for _ in range(self.u2()):
+            name = self.string(self.u2())
+            length = self.u4()
+            end = self.offset + length
+            if name in [
+                        'RuntimeVisibleAnnotations',
+                        'RuntimeInvisibleAnnotations'
+                    ]:
+                for _ in range(self.u2()):
+                    annotations.append(self.annotation())
+            self.offset = end
+        return annotations
+
+
+def parse_class(data: bytes) -> ClassFile:
+    reader = Reader(data)
+    if reader.u4() != 0xCAFEBABE:
+        raise ClassFormatError('not a class file')
+    _, major_version = reader.unpack('>HH')
+    reader.constant_pool()
+
+    _, this_class, _ = reader.unpack('>HHH')
+    result = ClassFile(major_version, reader.class_name(this_class))
+    reader.skip(2 * reader.u2())  # interfaces
+
+    for _ in range(reader.u2()):  # fields
+        reader.skip(6)
+        reader.attributes()
+    for _ in range(reader.u2()):
+        _, name, descriptor = reader.unpack('>HHH')
+        result.methods.append(Method(
+            reader.string(name),
+            reader.string(descriptor),
+            reader.attributes()
+        ))
+    result.annotations = reader.attributes()
+
+    return result
+
+
+def find_annotation(
+            annotations: List[Annotation],
+            type: str
+        ) -> Optional[Annotation]:
+    for annotation in annotations:
+        if annotation.type == type:
+            return annotation
+    return None
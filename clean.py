@@ -0,0 +1,160 @@
+
+from typing import Optional, Set
+import shutil
+import os
+import re
+
+from filesystem import DirectoryReal
+from mod_info import ModPack
+from output import CleanReport
+
+
+# configs belonging to the game or loader rather than to a mod, normalized
+SHARED_CONFIGS = [
+    'minecraft', 'forge', 'fml', 'neoforge', 'fabric', 'fabricloader',
+    'fabricloaderdependencies', 'quilt', 'quiltloader', 'mixin', 'global',
+]
+
+CONFIG_EXTENSIONS = [
+    'toml', 'json', 'json5', 'jsonc', 'cfg', 'conf', 'properties', 'txt',
+    'yml', 'yaml', 'snbt', 'ini',
+]
+
+# common suffixes mods attach to their modid when naming config files
+CONFIG_SUFFIXES = ['client', 'common', 'server', 'startup', 'config']
+
+
+def normalize(text: str) -> str:
+    return re.sub(r'[^a-z0-9]', '', text.lower())
+
+
+# the modid a config entry is named after, or None when it can't be told
+def config_modid(name: str, is_dir: bool) -> Optional[str]:
+    if name.startswith('.'):
+        return None
+
+    stem = name
+    if not is_dir:
+        stem, extension = os.path.splitext(name)
+        if extension.lstrip('.').lower() not in CONFIG_EXTENSIONS:
+            return None
+
+    suffixes = '|'.join(CONFIG_SUFFIXES)
+    match = re.fullmatch(rf'([a-z0-9_]+?)(?:[-_.](?:{suffixes}))?', stem)
+    if not match or len(match.groups()[0]) < 3:
+        return None
+    return match.groups()[0]
+
+
+def mod_keys(pack: ModPack) -> Set[str]:
+    keys: Set[str] = set()
+    for mod in pack.mods.values():
+        keys.add(normalize(mod.modid))
+        keys.add(normalize(mod.name))
+
+    # jars that failed to load or are disabled still own their configs
+    mod_dir = DirectoryReal(pack.directory, 'mods')
+    if os.path.isdir(mod_dir.full_path):
+        for filename in os.listdir(mod_dir.full_path):
+            stem = re.split(r'[-_ ]?v?[0-9]', filename, maxsplit=1)[0]
+            keys.add(normalize(stem))
+
+    return set([key for key in keys if len(key) >= 3])
+
+
+def is_associated(modid: str, keys: Set[str]) -> bool:
+    key = normalize(modid)
+    for mod_key in keys:
+        if key == mod_key or key.startswith(mod_key):
+            return True
+        if mod_key.startswith(key):
+            return True
+    return False
+
+
+def clean(pack: ModPack, confirm: bool) -> CleanReport:
+    report = CleanReport(not confirm)
+
+    config_dir = DirectoryReal(pack.directory, 'config')
+    if not os.path.isdir(config_dir.full_path):
+        return report
+
+    keys = mod_keys(pack)
+    for name in sorted(os.listdir(config_dir.full_path)):
+        path = os.path.join(config_dir.full_path, name)
+        is_dir = os.path.isdir(path)
+
+        modid = config_modid(name, is_dir)
+        if modid is None:
+            report.skipped.append(name)
+            continue
+        if normalize(modid) in SHARED_CONFIGS:
+            continue
+        if is_associated(modid, keys):
+            continue
+
+        report.removed.append(name)
+        if confirm:
+            if is_dir:
+                shutil.rmtree(path)
+            else:
+                os.remove(path)
+
+    return report
+
+
+def test():
+    from zipfile import ZipFile
+    import tempfile
+
+    with tempfile.TemporaryDirectory() as profile:
+        os.makedirs(os.path.join(profile, 'mods'))
+        jar_path = os.path.join(profile, 'mods', 'jei-15.1.jar')
+        with ZipFile(jar_path, 'w') as jar:
+            jar.writestr('META-INF/mods.toml', '\n'.join([
+                '[[mods]]',
+                'modId="jei"',
+                'version="15.1.0"',
+            ]))
+
+        config = os.path.join(profile, 'config')
+        os.makedirs(os.path.join(config, 'removedmod'))
+        for name in [
+                    'jei-client.toml',
+                    'forge-common.toml',
+                    'removedmod/settings.json',
+                    'orphaned-common.toml',
+                    'Weird.cfg',
+                ]:
+            with open(os.path.join(config, name), 'w') as file:
+                file.write('')
+
+        pack = ModPack(DirectoryReal(None, profile), 'forge', False)
+        pack.load(1, None, False)
+
+        print('===========================================================')
+        report = clean(pack, False)
+        print(report)
+        print(
+            '[PASS]' if report.removed == [
+                'orphaned-common.toml', 'removedmod',
+            ] and sorted(os.listdir(config)) == [
+                'Weird.cfg', 'forge-common.toml', 'jei-client.toml',
+                'orphaned-common.toml', 'removedmod',
+            ] else '[FAIL]'
+        )
+        print('===========================================================')
+        print(report.skipped)
+        print('[PASS]' if report.skipped == ['Weird.cfg'] else '[FAIL]')
+        print('===========================================================')
+        report = clean(pack, True)
+        print(sorted(os.listdir(config)))
+        print(
+            '[PASS]' if sorted(os.listdir(config)) == [
+                'Weird.cfg', 'forge-common.toml', 'jei-client.toml',
+            ] else '[FAIL]'
+        )
+
+
+if __name__ == '__main__':
+    test()
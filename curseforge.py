@@ -0,0 +1,137 @@
+
+from attrs import define, Factory
+
+from typing import Any, Dict, List, Optional, Tuple
+import json
+import os
+
+from filesystem import DirectoryReal
+from output import CurseForgeReport
+
+
+MANIFEST_FILE = 'manifest.json'
+
+
+@define
+class CurseForgeFile:
+    project_id: int
+    file_id:    int
+    required:   bool = True
+
+
+@define
+class CurseForgeManifest:
+    name:               Optional[str] = None
+    version:            Optional[str] = None
+    author:             Optional[str] = None
+    minecraft_version:  Optional[str] = None
+    # eg: `forge-47.2.0`
+    loaders:            List[str] = Factory(list)
+    files:              List[CurseForgeFile] = Factory(list)
+    overrides:          str = 'overrides'
+
+    @property
+    def primary_loader(self) -> Optional[str]:
+        return self.loaders[0] if len(self.loaders) > 0 else None
+
+
+def parse_manifest(data: Any) -> Tuple[CurseForgeManifest, List[str]]:
+    manifest = CurseForgeManifest()
+    errors: List[str] = []
+
+    if not isinstance(data, dict):
+        return manifest, ['manifest is not a json object']
+
+    def field(
+                container: Dict[str, Any],
+                key: str,
+                kind: type,
+                path: str
+            ) -> Any:
+        if key not in container:
+            errors.append(f"missing required field '{path}'")
+            return None
+        if not isinstance(container[key], kind):
+            errors.append(
+                f"field '{path}' should be of type {kind.__name__}"
+            )
+            return None
+        return container[key]
+
+    manifest_type = field(data, 'manifestType', str, 'manifestType')
+    if manifest_type is not None and manifest_type != 'minecraftModpack':
+        errors.append(
+            f"unexpected manifestType '{manifest_type}', expected "
+            f"'minecraftModpack'"
+        )
+    field(data, 'manifestVersion', int, 'manifestVersion')
+    manifest.name = field(data, 'name', str, 'name')
+    manifest.version = data.get('version', None)
+    manifest.author = data.get('author', None)
+    manifest.overrides = data.get('overrides', 'overrides')
+
+    minecraft = field(data, 'minecraft', dict, 'minecraft')
+    if minecraft is not None:
+        manifest.minecraft_version = field(
+            minecraft, 'version', str, 'minecraft.version'
+        )
+        loaders = field(minecraft, 'modLoaders', list, 'minecraft.modLoaders')
+        for i, loader in enumerate(loaders or []):
+            path = f'minecraft.modLoaders[{i}]'
+            if not isinstance(loader, dict):
+                errors.append(f"'{path}' is not a json object")
+                continue
+            loader_id = field(loader, 'id', str, f'{path}.id')
+            if loader_id is None:
+                continue
+            # the primary loader goes first
+            if loader.get('primary', False):
+                manifest.loaders.insert(0, loader_id)
+            else:
+                manifest.loaders.append(loader_id)
+        if loaders is not None and len(manifest.loaders) == 0:
+            errors.append("'minecraft.modLoaders' declares no loader")
+
+    files = field(data, 'files', list, 'files')
+    for i, entry in enumerate(files or []):
+        path = f'files[{i}]'
+        if not isinstance(entry, dict):
+            errors.append(f"'{path}' is not a json object")
+            continue
+        project_id = field(entry, 'projectID', int, f'{path}.projectID')
+        file_id = field(entry, 'fileID', int, f'{path}.fileID')
+        if project_id is None or file_id is None:
+            continue
+        manifest.files.append(CurseForgeFile(
+            project_id,
+            file_id,
+            bool(entry.get('required', True))
+        ))
+
+    return manifest, errors
+
+
+def has_manifest(directory: DirectoryReal) -> bool:
+    return directory.has(MANIFEST_FILE)
+
+
+def check_manifest(directory: DirectoryReal) -> CurseForgeReport:
+    path = os.path.join(directory.full_path, MANIFEST_FILE)
+    report = CurseForgeReport(path)
+
+    try:
+        with open(path, 'r', encoding='utf-8') as file:
+            data = json.load(file)
+    except (OSError, ValueError) as e:
+        report.errors.append(f'failed to read manifest: {e}')
+        return report
+
+    manifest, report.errors = parse_manifest(data)
+    report.name = manifest.name
+    report.version = manifest.version
+    report.minecraft_version = manifest.minecraft_version
+    report.loader = manifest.primary_loader
+    report.files = len(manifest.files)
+    report.optional_files = len([x for x in manifest.files if not x.required])
+    report.valid = len(report.errors) == 0
+    return report
@@ -0,0 +1,277 @@
+
+from attrs import define
+
+from typing import Any, Callable, Dict, List, Optional
+from zipfile import ZipFile, BadZipFile
+import logging
+import json
+import os
+
+from version import Version, BadVersionString
+
+
+log = logging.getLogger(__name__)
+
+# `mmc-pack.json` components of multimc and prism, by the modid they set
+MMC_COMPONENTS: Dict[str, str] = {
+    'net.minecraft': 'minecraft',
+    'net.minecraftforge': 'forge',
+    'net.neoforged': 'neoforge',
+    'net.fabricmc.fabric-loader': 'fabricloader',
+    'org.quiltmc.quilt-loader': 'quilt_loader',
+}
+
+# loader libraries in the version jsons of the vanilla launcher, and in the
+# `libraries/` of servers
+LOADER_LIBRARIES: Dict[str, str] = {
+    'net.minecraftforge:forge': 'forge',
+    'net.neoforged:neoforge': 'neoforge',
+    'net.fabricmc:fabric-loader': 'fabricloader',
+    'org.quiltmc:quilt-loader': 'quilt_loader',
+}
+
+# the launcher jars of fabric and quilt servers, which ship the versions
+# they install in `install.properties`
+SERVER_LAUNCHERS = ['fabric-server-launch.jar', 'quilt-server-launch.jar']
+
+
+@define
+class DetectedVersions:
+    # eg: `mmc-pack.json`
+    source:             str
+    minecraft:          Optional[str] = None
+    # the modid of the loader, eg: `fabricloader`
+    loader:             Optional[str] = None
+    loader_version:     Optional[str] = None
+
+    # what `--override-versions` would set, skipping unparsable versions
+    def overrides(self) -> Dict[str, Version]:
+        versions: Dict[str, Version] = {}
+        for modid, version in [
+                    ('minecraft', self.minecraft),
+                    (self.loader, self.loader_version)
+                ]:
+            if modid is None or version is None:
+                continue
+            try:
+                versions[modid] = Version.fromString(version)
+            except BadVersionString:
+                log.debug(f"{self.source}: can't parse {modid} {version}")
+        return versions
+
+
+def read_json(path: str) -> Any:
+    try:
+        with open(path, 'r') as file:
+            return json.load(file)
+    except (OSError, ValueError) as e:
+        log.debug(f"'{path}': {e}")
+        return None
+
+
+# `key=value` lines, as in `instance.cfg` and `.properties` files
+def parse_properties(text: str) -> Dict[str, str]:
+    properties: Dict[str, str] = {}
+    for line in text.splitlines():
+        line = line.strip()
+        if line == '' or line[0] in '#!' or '=' not in line:
+            continue
+        key, value = line.split('=', 1)
+        properties[key.strip()] = value.strip()
+    return properties
+
+
+# neoforge versions start with the minecraft version, eg: `20.4.80` is for
+# `1.20.4` and `21.0.10` for `1.21`
+def neoforge_minecraft(version: str) -> Optional[str]:
+    parts = version.split('.')
+    if len(parts) < 2 or not all([x.isdigit() for x in parts[:2]]):
+        return None
+    if parts[1] == '0':
+        return f'1.{parts[0]}'
+    return f'1.{parts[0]}.{parts[1]}'
+
+
+# forge versions are `<minecraft>-<forge>` in library names
+def library_version(
+            detected: DetectedVersions,
+            loader: str,
+            version: str
+        ) -> None:
+    detected.loader = loader
+    detected.loader_version = version
+    if loader == 'forge' and '-' in version:
+        minecraft, detected.loader_version = version.split('-', 1)
+        detected.minecraft = detected.minecraft or minecraft
+    elif loader == 'neoforge':
+        detected.minecraft = detected.minecraft or neoforge_minecraft(version)
+
+
+# multimc and prism keep the profile in `.minecraft/` of the instance
+def instance_dirs(profile_dir: str) -> List[str]:
+    return [profile_dir, os.path.dirname(os.path.normpath(profile_dir))]
+
+
+def from_mmc_pack(profile_dir: str) -> Optional[DetectedVersions]:
+    for directory in instance_dirs(profile_dir):
+        path = os.path.join(directory, 'mmc-pack.json')
+        if not os.path.isfile(path):
+            continue
+        data = read_json(path)
+        if not isinstance(data, dict):
+            continue
+
+        detected = DetectedVersions(path)
+        components = data.get('components', [])
+        for component in components if isinstance(components, list) else []:
+            if not isinstance(component, dict):
+                continue
+            modid = MMC_COMPONENTS.get(component.get('uid', ''), None)
+            version = component.get('version', None)
+            if modid is None or not isinstance(version, str):
+                continue
+            if modid == 'minecraft':
+                detected.minecraft = version
+            else:
+                detected.loader = modid
+                detected.loader_version = version
+        return detected
+    return None
+
+
+# older multimc instances only have `IntendedVersion` for minecraft
+def from_instance_cfg(profile_dir: str) -> Optional[DetectedVersions]:
+    for directory in instance_dirs(profile_dir):
+        path = os.path.join(directory, 'instance.cfg')
+        try:
+            with open(path, 'r') as file:
+                properties = parse_properties(file.read())
+        except OSError:
+            continue
+        if properties.get('IntendedVersion', ''):
+            return DetectedVersions(path, properties['IntendedVersion'])
+    return None
+
+
+# the version the vanilla launcher last started for the profile, or the
+# only one with a loader
+def from_launcher_versions(profile_dir: str) -> Optional[DetectedVersions]:
+    versions_dir = os.path.join(profile_dir, 'versions')
+    if not os.path.isdir(versions_dir):
+        return None
+
+    ids: List[str] = []
+    path = os.path.join(profile_dir, 'launcher_profiles.json')
+    profiles = read_json(path) if os.path.isfile(path) else None
+    if isinstance(profiles, dict):
+        profiles = profiles.get('profiles', None)
+    if isinstance(profiles, dict):
+        for profile in profiles.values():
+            if not isinstance(profile, dict):
+                continue
+            game_dir = str(profile.get('gameDir', profile_dir))
+            if os.path.normpath(game_dir) == os.path.normpath(profile_dir):
+                ids.append(str(profile.get('lastVersionId', '')))
+    if len(ids) == 0:
+        ids = sorted(os.listdir(versions_dir))
+
+    found: List[DetectedVersions] = []
+    for version_id in ids:
+        path = os.path.join(versions_dir, version_id, f'{version_id}.json')
+        data = read_json(path) if os.path.isfile(path) else None
+        if not isinstance(data, dict):
+            continue
+
+        detected = DetectedVersions(path, data.get('inheritsFrom', None))
+        libraries = data.get('libraries', [])
+        for library in libraries if isinstance(libraries, list) else []:
+            if not isinstance(library, dict):
+                continue
+            name = str(library.get('name', ''))
+            for prefix, loader in LOADER_LIBRARIES.items():
+                if name.startswith(f'{prefix}:'):
+                    version = name[len(prefix) + 1:].split(':')[0]
+                    library_version(detected, loader, version)
+        if detected.loader is not None:
+            found.append(detected)
+
+    return found[0] if len(found) == 1 else None
+
+
+def from_server_launcher(profile_dir: str) -> Optional[DetectedVersions]:
+    for name in SERVER_LAUNCHERS:
+        path = os.path.join(profile_dir, name)
+        try:
+            with ZipFile(path, 'r') as jar:
+                properties = parse_properties(
+                    jar.read('install.properties').decode(errors='replace')
+                )
+        except (OSError, BadZipFile, KeyError):
+            continue
+
+        detected = DetectedVersions(
+            f'{path}!install.properties',
+            properties.get('game-version', None)
+        )
+        for key, modid in [
+                    ('fabric-loader-version', 'fabricloader'),
+                    ('quilt-loader-version', 'quilt_loader')
+                ]:
+            if key in properties:
+                detected.loader = modid
+                detected.loader_version = properties[key]
+        return detected
+    return None
+
+
+# forge and neoforge servers install their loader in `libraries/`, eg:
+# `libraries/net/minecraftforge/forge/1.20.1-47.2.0/`
+def from_server_libraries(profile_dir: str) -> Optional[DetectedVersions]:
+    for prefix, loader in LOADER_LIBRARIES.items():
+        group, artifact = prefix.split(':')
+        path = os.path.join(
+            profile_dir,
+            'libraries',
+            *group.split('.'),
+            artifact
+        )
+        try:
+            versions = sorted(os.listdir(path))
+        except OSError:
+            continue
+        if len(versions) != 1:
+            log.debug(f"'{path}': {len(versions)} versions, skipping")
+            continue
+        detected = DetectedVersions(os.path.join(path, versions[0]))
+        library_version(detected, loader, versions[0])
+        return detected
+    return None
+
+
+DETECTORS: List[Callable[[str], Optional[DetectedVersions]]] = [
+    from_mmc_pack,
+    from_instance_cfg,
+    from_launcher_versions,
+    from_server_launcher,
+    from_server_libraries,
+]
+
+
+# the minecraft and loader versions of the profile, from the first of these
+# that knows them
+def detect_profile_versions(profile_dir: str) -> Optional[DetectedVersions]:
+    for detector in DETECTORS:
+        detected = detector(profile_dir)
+        if detected is None:
+            continue
+        if detected.minecraft is None and detected.loader is None:
+            log.debug(f'{detected.source}: no versions found')
+            continue
+        log.debug(
+            f'detected minecraft {detected.minecraft or "unknown"} and '
+            f'{detected.loader or "no loader"} '
+            f'{detected.loader_version or ""} from {detected.source}'
+        )
+        return detected
+    log.debug(f"'{profile_dir}': no versions detected")
+    return None
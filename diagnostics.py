@@ -0,0 +1,386 @@
+
+from attrs import define
+
+from typing import Dict, List, Optional, Set, Tuple
+from difflib import SequenceMatcher
+import re
+import os
+
+from mod_info import ModPack
+from fuzzy import closest_modids
+from output import (
+    FindErrorReport, ErrorMatchReport, MentionedModReport, CrashLogReport,
+    LogBlockReport, CulpritReport
+)
+
+
+@define
+class KnownError:
+    name:           str
+    pattern:        str
+    explanation:    str
+    fix:            str
+    # a typical message, used when suggesting close matches
+    example:        str
+
+    def match(self, error: str) -> Optional[re.Match]:
+        return re.search(self.pattern, error, re.DOTALL)
+
+
+# named groups `modid`, `dep` and `culprit` are looked up in the pack
+KNOWN_ERRORS: List[KnownError] = [
+    KnownError(
+        'missing mandatory dependency (forge)',
+        r"Missing or unsupported mandatory dependencies"
+        r"(?:.*?Mod ID: '(?P<dep>[^']+)', Requested by: '(?P<modid>[^']+)')?",
+        'a mod requires another mod (or a different version of it) that '
+        'is not installed',
+        'install the requested mod in a version within the expected range, '
+        'or remove the mod requesting it',
+        "Missing or unsupported mandatory dependencies: Mod ID: 'foo', "
+        "Requested by: 'bar', Expected range: '[1.0,)'"
+    ),
+    KnownError(
+        'missing dependency (fabric)',
+        r"Mod '[^']*' \((?P<modid>[^)]+)\) \S+ requires .*?"
+        r"'[^']*' \((?P<dep>[^)]+)\)",
+        'a mod requires another mod (or a different version of it) that '
+        'is not installed',
+        'install the required mod in a compatible version, or remove the '
+        'mod requiring it',
+        "Mod 'Foo' (foo) 1.0.0 requires any version of 'Bar' (bar), "
+        "which is missing!"
+    ),
+    KnownError(
+        'duplicate mods',
+        r"(?:Found duplicate mods|Duplicate mods found)"
+        r"(?:.*?Mod ID: '(?P<modid>[^']+)')?",
+        'more than one jar provides the same mod',
+        'keep only one version of the mod in the mods folder',
+        "Found duplicate mods: Mod ID: 'foo' from mod files: foo-1.0.jar, "
+        "foo-1.1.jar"
+    ),
+    KnownError(
+        'mixin apply failure',
+        r"Mixin apply(?: for mod (?P<modid>[a-z][a-z0-9_-]*))? failed",
+        'a mod failed to patch a game or mod class, usually because the '
+        'targeted code differs from what the mod was built against',
+        'update the mod mentioned, or check whether another mod conflicts '
+        'with it by patching the same class',
+        "Mixin apply for mod foo failed foo.mixins.json:FooMixin from mod foo"
+    ),
+    KnownError(
+        'method not found',
+        r"java\.lang\.NoSuchMethodError",
+        'a mod called a method that does not exist, so it was built '
+        'against a different version of minecraft or of one of its '
+        'dependencies',
+        'make sure the mod and its dependencies are versions made for each '
+        'other and for this minecraft version',
+        "java.lang.NoSuchMethodError: 'void net.minecraft.Foo.bar()'"
+    ),
+    KnownError(
+        'field not found',
+        r"java\.lang\.NoSuchFieldError",
+        'a mod accessed a field that does not exist, so it was built '
+        'against a different version of minecraft or of one of its '
+        'dependencies',
+        'make sure the mod and its dependencies are versions made for each '
+        'other and for this minecraft version',
+        "java.lang.NoSuchFieldError: FOO"
+    ),
+    KnownError(
+        'class not found',
+        r"java\.lang\.(?:NoClassDefFoundError|ClassNotFoundException)",
+        'a mod references a class that is not available, usually from a '
+        'missing dependency or a client-only class on a server',
+        'install the missing dependency, or remove client-only mods from '
+        'servers',
+        "java.lang.NoClassDefFoundError: com/example/foo/Bar"
+    ),
+    KnownError(
+        'client class on server',
+        r"Attempted to load class \S+ for invalid dist DEDICATED_SERVER",
+        'a client-only mod (or client-only code in a mod) was loaded on a '
+        'dedicated server',
+        'remove client-only mods from the server',
+        "Attempted to load class net/minecraft/client/Minecraft for invalid "
+        "dist DEDICATED_SERVER"
+    ),
+    KnownError(
+        'unsupported java version',
+        r"java\.lang\.UnsupportedClassVersionError",
+        'a mod was compiled for a newer java version than the one running '
+        'the game',
+        'run the game with a newer java version',
+        "java.lang.UnsupportedClassVersionError: com/example/Foo has been "
+        "compiled by a more recent version of the Java Runtime"
+    ),
+    KnownError(
+        'mod construction failure',
+        r"(?:Failed to create mod instance\. ModID: (?P<modid>[a-z0-9_-]+)|"
+        r"Caught exception from [^(]+\((?P<culprit>[a-z0-9_-]+)\))",
+        'a mod crashed while being loaded',
+        'check the rest of the log for the cause, and update or remove '
+        'the mod',
+        "Failed to create mod instance. ModID: foo"
+    ),
+    KnownError(
+        'incompatible mods',
+        r"(?:Incompatible mods? found|is incompatible with)",
+        'a mod declares that it cannot be used together with another '
+        'installed mod',
+        'remove one of the two mods',
+        "Incompatible mods found! Mod 'Foo' (foo) is incompatible with "
+        "any version of mod 'Bar' (bar)"
+    ),
+    KnownError(
+        'out of memory',
+        r"java\.lang\.OutOfMemoryError",
+        'the game ran out of memory',
+        'allocate more memory to the game (-Xmx), or remove mods',
+        "java.lang.OutOfMemoryError: Java heap space"
+    ),
+]
+
+
+def find_modids(pack: ModPack, error: str, match: re.Match) -> List[str]:
+    modids: List[str] = []
+    for group in ['modid', 'dep', 'culprit']:
+        modid = match.groupdict().get(group, None)
+        if modid and modid not in modids:
+            modids.append(modid)
+
+    # otherwise look for installed mods mentioned in the message itself
+    words = set(re.findall(r'[a-z][a-z0-9_-]{2,}', error.lower()))
+    for modid in pack.mods.keys():
+        if modid in words and modid not in modids:
+            modids.append(modid)
+
+    return modids
+
+
+def closest_known_errors(error: str, n: int = 3) -> List[KnownError]:
+    scores: List[Tuple[float, KnownError]] = []
+    for known in KNOWN_ERRORS:
+        score = max(
+            SequenceMatcher(None, error.lower(), known.example.lower())
+            .ratio(),
+            SequenceMatcher(None, error.lower(), known.name).ratio()
+        )
+        scores.append((score, known))
+    scores.sort(key=lambda x: x[0], reverse=True)
+    return [known for _, known in scores[:n]]
+
+
+def find_error(pack: ModPack, error: str) -> FindErrorReport:
+    report = FindErrorReport(error)
+
+    for known in KNOWN_ERRORS:
+        match = known.match(error)
+        if not match:
+            continue
+
+        result = ErrorMatchReport(
+            known.name,
+            known.explanation,
+            known.fix,
+            known.example
+        )
+        for modid in find_modids(pack, error, match):
+            if modid in pack.mods:
+                mod = pack.mods[modid]
+                result.mods.append(MentionedModReport(
+                    modid,
+                    True,
+                    str(mod._version),
+                    mod.filename
+                ))
+            else:
+                mentioned = MentionedModReport(modid, False)
+                mentioned.suggestions = [
+                    x for x, _ in closest_modids(modid, pack.mods.keys())
+                ]
+                result.mods.append(mentioned)
+        report.matches.append(result)
+
+    if len(report.matches) == 0:
+        for known in closest_known_errors(error):
+            report.suggestions.append(ErrorMatchReport(
+                known.name,
+                known.explanation,
+                known.fix,
+                known.example
+            ))
+
+    return report
+
+
+# lines starting an error block, eg: `[12:00:00] [main/ERROR]: ...` or
+# `java.lang.NullPointerException: ...`
+EXCEPTION = re.compile(
+    r'\b(?:[a-z_][\w$]*\.)+[A-Z][\w$]*(?:Exception|Error)\b'
+)
+LOG_ERROR = re.compile(r'[/ ](?:ERROR|FATAL)\]')
+# stack frames, causes and anything indented belongs to the block above
+BLOCK_CONTINUATION = re.compile(r'^(?:\s+\S|Caused by:|Suppressed:)')
+STACK_FRAME = re.compile(r'^\s*at (?P<location>[\w$.<>/]+)\(')
+# log prefixes, so repeated errors can be told apart from new ones
+LOG_PREFIX = re.compile(r'^(?:\[[^\]]*\]\s*:?\s*)+')
+# `Suspected Mods: Create (create), Flywheel (flywheel)`
+SUSPECTED_MODS = re.compile(r'Suspected Mods?:')
+
+# how much of a log is shown
+MAX_BLOCKS = 10
+MAX_BLOCK_LINES = 15
+MAX_CULPRITS = 10
+# modids too common in stack traces to blame
+IGNORED_CULPRITS = [
+    'minecraft', 'forge', 'neoforge', 'fabricloader', 'quilt_loader', 'java',
+]
+
+# how much each kind of evidence counts towards a culprit, once per block
+REASON_SCORES: Dict[str, int] = {
+    'suspected by the crash report': 5,
+    'errored in the crash report mod list': 5,
+    'named by a known error': 3,
+    'jar in stack frames': 2,
+    'mixin in stack frames': 2,
+    'package in stack frames': 1,
+}
+
+
+# error blocks of the log with repeated stack frames removed, and how many
+# times each appears
+def split_blocks(text: str) -> List[Tuple[List[str], int]]:
+    blocks: Dict[str, Tuple[List[str], int]] = {}
+    current: Optional[List[str]] = None
+    seen: Set[str] = set()
+
+    def finish(lines: Optional[List[str]]) -> None:
+        if lines is None:
+            return
+        key = '\n'.join([LOG_PREFIX.sub('', lines[0])] + lines[1:])
+        found, count = blocks.get(key, (lines, 0))
+        blocks[key] = (found, count + 1)
+
+    for line in text.splitlines():
+        line = line.rstrip()
+        # the exception logged by the line above
+        thrown = (
+            current is not None and len(current) == 1
+            and EXCEPTION.search(line) is not None
+            and LOG_PREFIX.match(line) is None
+        )
+        if current is not None and (
+                    thrown or BLOCK_CONTINUATION.match(line)
+                ):
+            if line not in seen:
+                current.append(line)
+                seen.add(line)
+            continue
+        finish(current)
+        start = EXCEPTION.search(line) or LOG_ERROR.search(line)
+        current = [line] if start else None
+        seen = set(current or [])
+    finish(current)
+
+    return list(blocks.values())
+
+
+# modids from a crash report's suspected mods and the mods that errored in
+# its mod list, eg: `create-0.5.1.jar |Create |create |0.5.1 |ERROR`
+def crash_report_mods(text: str) -> Dict[str, str]:
+    mods: Dict[str, str] = {}
+    lines = text.splitlines()
+    for i, line in enumerate(lines):
+        if SUSPECTED_MODS.search(line):
+            # either on the same line or indented below it
+            suspected = ' '.join([line] + [
+                x for x in lines[i + 1:i + 6] if x.startswith('\t\t')
+            ])
+            for modid in re.findall(r'\(([a-z][a-z0-9_-]*)\)', suspected):
+                mods.setdefault(modid, 'suspected by the crash report')
+
+        columns = [x.strip() for x in line.split('|')]
+        if len(columns) >= 5 and columns[4] == 'ERROR':
+            mods.setdefault(
+                columns[2],
+                'errored in the crash report mod list'
+            )
+    return mods
+
+
+def frame_mods(pack: ModPack, line: str) -> Dict[str, str]:
+    mods: Dict[str, str] = {}
+    frame = STACK_FRAME.match(line)
+    if frame is None:
+        return mods
+
+    jars = re.findall(r'[\w.+-]+\.jar', line)
+    configs = re.findall(r'[\w.-]+\.json', line)
+    packages = frame.group('location').replace('/', '.').split('.')[:-2]
+    for mod in pack.mods.values():
+        if mod.info is None or mod.modid in IGNORED_CULPRITS:
+            continue
+        if os.path.basename(mod.jar_file) in jars:
+            mods.setdefault(mod.modid, 'jar in stack frames')
+        elif any([x in mod.info.mixin_configs for x in configs]):
+            mods.setdefault(mod.modid, 'mixin in stack frames')
+        elif mod.modid in packages:
+            mods.setdefault(mod.modid, 'package in stack frames')
+    return mods
+
+
+# the error blocks of a whole log or crash report explained, and the
+# installed mods most likely behind them
+def find_log_errors(pack: ModPack, text: str, path: str) -> CrashLogReport:
+    report = CrashLogReport(path)
+    culprits: Dict[str, CulpritReport] = {}
+
+    def blame(modid: str, reason: str) -> None:
+        mod = pack.mods.get(modid, None)
+        if mod is None or mod.info is None or modid in IGNORED_CULPRITS:
+            return
+        culprit = culprits.setdefault(
+            modid,
+            CulpritReport(modid, str(mod._version), mod.filename)
+        )
+        culprit.reasons[reason] = culprit.reasons.get(reason, 0) + 1
+        culprit.score += REASON_SCORES[reason]
+
+    for modid, reason in crash_report_mods(text).items():
+        blame(modid, reason)
+
+    blocks = split_blocks(text)
+    report.total = len(blocks)
+    for lines, count in blocks:
+        block = LogBlockReport(lines[:MAX_BLOCK_LINES], count)
+        block.hidden = len(lines) - len(block.lines)
+        block.matches = find_error(pack, '\n'.join(lines)).matches
+
+        # only the strongest evidence of each mod in the block counts
+        blamed: Dict[str, str] = {}
+        found = [
+            x for line in lines for x in frame_mods(pack, line).items()
+        ] + [
+            (x.modid, 'named by a known error')
+            for match in block.matches for x in match.mods
+        ]
+        for modid, reason in found:
+            score = REASON_SCORES[blamed[modid]] if modid in blamed else 0
+            if REASON_SCORES[reason] > score:
+                blamed[modid] = reason
+        for modid, reason in blamed.items():
+            blame(modid, reason)
+        report.blocks.append(block)
+
+    # the ones explained by a known error first
+    report.blocks.sort(key=lambda x: len(x.matches) == 0)
+    report.blocks = report.blocks[:MAX_BLOCKS]
+
+    report.culprits = sorted(
+        culprits.values(),
+        key=lambda x: (-x.score, x.modid)
+    )[:MAX_CULPRITS]
+    return report
@@ -0,0 +1,27 @@
+
+from mod_info import ModPack
+from output import DiffReport, DiffModReport, VersionChangeReport
+
+
+def diff_packs(old: ModPack, new: ModPack) -> DiffReport:
+    report = DiffReport(old.directory.full_path, new.directory.full_path)
+
+    for modid in sorted(set(old.mods.keys()) | set(new.mods.keys())):
+        if modid not in old.mods:
+            mod = new.mods[modid]
+            report.added.append(
+                DiffModReport(modid, str(mod._version), mod.filename)
+            )
+        elif modid not in new.mods:
+            mod = old.mods[modid]
+            report.removed.append(
+                DiffModReport(modid, str(mod._version), mod.filename)
+            )
+        elif old.mods[modid]._version != new.mods[modid]._version:
+            report.changed.append(VersionChangeReport(
+                modid,
+                str(old.mods[modid]._version),
+                str(new.mods[modid]._version)
+            ))
+
+    return report
@@ -0,0 +1,56 @@
+
+from typing import Set, TextIO
+
+import graph
+from mod_info import ModPack
+from output import GraphReport
+
+
+# any modid becomes a valid quoted DOT id
+def quote(text: str) -> str:
+    return '"' + text.replace('\\', '\\\\').replace('"', '\\"') + '"'
+
+
+# writes as it goes, so large packs never end up in a single string
+def write_dot(pack: ModPack, file: TextIO, output: str) -> GraphReport:
+    report = GraphReport(output)
+    dependency_graph = graph.DependencyGraph(pack.mods, pack.aliases)
+
+    file.write('digraph mods {\n')
+    file.write('    node [shape=ellipse];\n')
+
+    missing: Set[str] = set()
+    for modid in sorted(pack.mods.keys()):
+        mod = pack.mods[modid]
+        label = quote(f'{modid}@{mod._version}')
+        # stand-ins provided by `--override-versions`
+        shape = ', shape=box' if mod.info is None else ''
+        file.write(f'    {quote(modid)} [label={label}{shape}];\n')
+        report.nodes += 1
+
+        for dep in mod.dependencies:
+            dep_modid = dependency_graph.resolve(dep.modid)
+            if dep_modid not in pack.mods:
+                if not dep.required:
+                    continue
+                missing.add(dep_modid)
+
+            if not dependency_graph.is_satisfied(modid, dep_modid):
+                style = 'color=red'
+            elif dep.required:
+                style = 'color=black'
+            else:
+                style = 'color=gray, style=dashed'
+            edge = f'{quote(modid)} -> {quote(dep_modid)}'
+            file.write(f'    {edge} [{style}];\n')
+            report.edges += 1
+
+    for modid in sorted(missing):
+        label = quote(f'{modid} (missing)')
+        file.write(
+            f'    {quote(modid)} [label={label}, style=dotted, color=red];\n'
+        )
+        report.nodes += 1
+
+    file.write('}\n')
+    return report
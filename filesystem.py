@@ -48,14 +48,14 @@ class FileBase(ABC):
     def read_large(self, buffer_size: int = 0) -> Generator[bytes, None, None]:
         if buffer_size <= 0:
             buffer_size = BUF_SIZE
-        while data := self._read(buffer_size):
-            yield cast(bytes, data)
+        yield from self._read(buffer_size)
 
-    def hash(self) -> str:
-        md5 = hashlib.md5(usedforsecurity=False)
+    # any algorithm supported by `hashlib`, eg: `sha1` or `sha512`
+    def hash(self, algorithm: str = 'md5') -> str:
+        digest = hashlib.new(algorithm, usedforsecurity=False)
         for chunk in self.read_large():
-            md5.update(chunk)
-        return md5.hexdigest()
+            digest.update(chunk)
+        return digest.hexdigest()
 
 
 @define
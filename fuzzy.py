@@ -0,0 +1,47 @@
+
+from typing import Iterable, List, Tuple
+
+
+# edit distance keeping only two rows around
+def levenshtein(a: str, b: str) -> int:
+    if len(a) < len(b):
+        a, b = b, a
+    previous = list(range(len(b) + 1))
+    current = [0] * (len(b) + 1)
+    for i, char_a in enumerate(a):
+        current[0] = i + 1
+        for j, char_b in enumerate(b):
+            current[j + 1] = min(
+                previous[j + 1] + 1,
+                current[j] + 1,
+                previous[j] + (char_a != char_b)
+            )
+        previous, current = current, previous
+    return previous[len(b)]
+
+
+# 1.0 for identical strings, 0.0 for entirely different ones
+def similarity(a: str, b: str) -> float:
+    if not a and not b:
+        return 1.0
+    return 1.0 - levenshtein(a, b) / max(len(a), len(b))
+
+
+def closest_modids(
+            query: str,
+            modids: Iterable[str],
+            n: int = 3,
+            threshold: float = 0.6
+        ) -> List[Tuple[str, float]]:
+    query = query.lower()
+    scores: List[Tuple[str, float]] = []
+    for modid in set(modids):
+        # the distance can't beat the difference in length
+        longest = max(len(query), len(modid))
+        if longest and abs(len(query) - len(modid)) / longest > 1 - threshold:
+            continue
+        score = similarity(query, modid.lower())
+        if score >= threshold and modid != query:
+            scores.append((modid, score))
+    scores.sort(key=lambda x: (-x[1], x[0]))
+    return scores[:n]
@@ -0,0 +1,275 @@
+
+import heapq
+from typing import TYPE_CHECKING, cast, Dict, List, Optional, Set, Tuple
+
+if TYPE_CHECKING:
+    from mod_info import Mod, ModDependency
+
+
+# dense packs have exponentially many chains to common dependencies
+MAX_CHAINS = 100
+
+
+class DependencyGraph:
+    mods:       Dict[str, 'Mod']
+    edges:      Dict[str, List['ModDependency']]
+    dependents: Dict[str, List[str]]
+    # modids provided by other mods, see `ModPack.aliases`
+    aliases:    Dict[str, str]
+
+    def __init__(
+                self,
+                mods: Dict[str, 'Mod'],
+                aliases: Optional[Dict[str, str]] = None
+            ):
+        self.mods = mods
+        self.aliases = aliases if aliases is not None else {}
+        self.edges = {}
+        self.dependents = {}
+
+        for mod in mods.values():
+            self.edges[mod.modid] = mod.dependencies
+            for dep in mod.dependencies:
+                dependents = self.dependents.setdefault(
+                    self.resolve(dep.modid),
+                    []
+                )
+                if mod.modid not in dependents:
+                    dependents.append(mod.modid)
+
+    # the modid of the mod satisfying dependencies on `modid`
+    def resolve(self, modid: str) -> str:
+        if modid not in self.mods and modid in self.aliases:
+            return self.aliases[modid]
+        return modid
+
+    def edge(self, modid: str, dep_modid: str) -> Optional['ModDependency']:
+        for dep in self.edges.get(modid, []):
+            if self.resolve(dep.modid) == dep_modid:
+                return dep
+        return None
+
+    def is_satisfied(self, modid: str, dep_modid: str) -> bool:
+        dep = self.edge(modid, dep_modid)
+        if dep is None or self.mods[modid].lie_depends:
+            return True
+        if dep_modid not in self.mods:
+            return not dep.required
+        return dep.validateMod(self.mods[dep_modid])
+
+    # chains of mods ending in `modid`, starting from a mod that nothing
+    # else depends on, eg: `[a, b, modid]` if `a -> b -> modid`. stops after
+    # `limit` of them, the bool is whether there were more
+    def reverse_dependents(
+                self,
+                modid: str,
+                limit: int = MAX_CHAINS
+            ) -> Tuple[List[List[str]], bool]:
+        chains: List[List[str]] = []
+        truncated = False
+
+        def walk(path: List[str]) -> None:
+            nonlocal truncated
+            if len(chains) >= limit:
+                truncated = True
+                return
+            parents = [
+                x for x in self.dependents.get(path[-1], []) if x not in path
+            ]
+            if len(parents) == 0:
+                if len(path) > 1:
+                    chains.append(list(reversed(path)))
+                return
+            for parent in sorted(parents):
+                walk(path + [parent])
+
+        walk([modid])
+        return chains, truncated
+
+    # (dependent, dependency) pairs along `chain` that aren't satisfied
+    def unsatisfied_edges(
+                self,
+                chain: List[str]
+            ) -> List[Tuple[str, 'ModDependency']]:
+        result: List[Tuple[str, 'ModDependency']] = []
+        for modid, dep_modid in zip(chain, chain[1:]):
+            dep = self.edge(modid, dep_modid)
+            if dep is not None and not self.is_satisfied(modid, dep_modid):
+                result.append((modid, dep))
+        return result
+
+    # every cycle between installed mods, eg: `[a, b, c]` if
+    # `a -> b -> c -> a`, each starting from its smallest modid
+    def find_cycles(self) -> List[List[str]]:
+        edges: Dict[str, List[str]] = {}
+        for modid, deps in self.edges.items():
+            edges[modid] = [
+                self.resolve(x.modid) for x in deps
+                if self.resolve(x.modid) in self.mods
+            ]
+        return find_cycles(edges)
+
+    # cycles made only of required dependencies can't be loaded at all,
+    # while ones through optional dependencies are usually metadata bugs
+    def is_mandatory_cycle(self, cycle: List[str]) -> bool:
+        for modid, dep_modid in zip(cycle, cycle[1:] + cycle[:1]):
+            dep = self.edge(modid, dep_modid)
+            if dep is None or not dep.required:
+                return False
+        return True
+
+    # `a -> b` when `a` has to load before `b`
+    def ordering_edges(self) -> Dict[str, List[str]]:
+        edges: Dict[str, List[str]] = {x: [] for x in self.mods.keys()}
+        for modid, deps in self.edges.items():
+            for dep in deps:
+                dep_modid = self.resolve(dep.modid)
+                if dep_modid not in self.mods or dep_modid == modid:
+                    continue
+                ordering = dep.ordering.upper()
+                if ordering == 'BEFORE':
+                    before, after = modid, dep_modid
+                elif ordering == 'AFTER':
+                    before, after = dep_modid, modid
+                else:
+                    continue
+                if after not in edges[before]:
+                    edges[before].append(after)
+        return edges
+
+    # the order mods load in, with `roots` already loaded, or the cycles of
+    # contradicting ordering constraints when there is no valid order
+    def load_order(
+                self,
+                roots: List[str]
+            ) -> Tuple[List[str], List[List[str]]]:
+        edges = self.ordering_edges()
+        incoming: Dict[str, int] = {x: 0 for x in edges.keys()}
+        for modid, afters in edges.items():
+            for after in afters:
+                incoming[after] += 1
+
+        order: List[str] = sorted(roots)
+        ready = [
+            x for x, count in incoming.items()
+            if count == 0 and x not in roots
+        ]
+        for root in order:
+            for after in edges[root]:
+                incoming[after] -= 1
+                if incoming[after] == 0 and after not in roots:
+                    ready.append(after)
+        heapq.heapify(ready)
+
+        while len(ready) > 0:
+            modid = heapq.heappop(ready)
+            order.append(modid)
+            for after in edges[modid]:
+                incoming[after] -= 1
+                if incoming[after] == 0 and after not in roots:
+                    heapq.heappush(ready, after)
+
+        if len(order) == len(edges):
+            return order, []
+
+        remaining = {
+            modid: [x for x in afters if x not in order]
+            for modid, afters in edges.items() if modid not in order
+        }
+        return [], find_cycles(remaining)
+
+
+# every cycle in `edges`, each starting from its smallest node
+def find_cycles(edges: Dict[str, List[str]]) -> List[List[str]]:
+    cycles: List[List[str]] = []
+    visited: Set[str] = set()
+    stack: List[str] = []
+
+    def visit(node: str) -> None:
+        visited.add(node)
+        stack.append(node)
+        for child in edges.get(node, []):
+            if child in stack:
+                cycle = stack[stack.index(child):]
+                start = cycle.index(min(cycle))
+                cycle = cycle[start:] + cycle[:start]
+                if cycle not in cycles:
+                    cycles.append(cycle)
+            elif child not in visited:
+                visit(child)
+        stack.pop()
+
+    for node in sorted(edges.keys()):
+        if node not in visited:
+            visit(node)
+    return cycles
+
+
+def test():
+    from mod_info import Mod, ModDependency, ModPack
+    mods: Dict[str, Mod] = {}
+    for modid, deps in [
+                ('a', [('b', True)]),
+                ('b', [('c', True)]),
+                ('c', [('a', True), ('d', True)]),
+                ('d', [('e', False)]),
+                ('e', [('d', True)]),
+                ('f', [('a', True), ('missing', True)]),
+            ]:
+        mod = Mod(cast(ModPack, None))
+        mod.modid = modid
+        mod.dependencies = [ModDependency(x, y, '*') for x, y in deps]
+        mods[modid] = mod
+
+    dependency_graph = DependencyGraph(mods)
+    cycles = dependency_graph.find_cycles()
+    print('===========================================================')
+    print(cycles)
+    print('[PASS]' if cycles == [['a', 'b', 'c'], ['d', 'e']] else '[FAIL]')
+    print('===========================================================')
+    print(['a', 'b', 'c'])
+    print(
+        '[PASS]' if dependency_graph.is_mandatory_cycle(['a', 'b', 'c'])
+        else '[FAIL]'
+    )
+    print('===========================================================')
+    print(['d', 'e'])
+    print(
+        '[PASS]' if not dependency_graph.is_mandatory_cycle(['d', 'e'])
+        else '[FAIL]'
+    )
+    print('===========================================================')
+    mods['a'].dependencies[0].ordering = 'AFTER'
+    mods['f'].dependencies[0].ordering = 'BEFORE'
+    order, conflicts = dependency_graph.load_order([])
+    print(order)
+    print('[PASS]' if order == ['b', 'c', 'd', 'e', 'f', 'a'] else '[FAIL]')
+    print('===========================================================')
+    mods['b'].dependencies[0].ordering = 'AFTER'
+    mods['c'].dependencies[0].ordering = 'AFTER'
+    order, conflicts = dependency_graph.load_order([])
+    print(conflicts)
+    print('[PASS]' if conflicts == [['a', 'c', 'b']] else '[FAIL]')
+    print('===========================================================')
+    # `f` depends on `missing`, which `e` provides
+    aliased = DependencyGraph(mods, {'missing': 'e'})
+    print(aliased.dependents['e'])
+    print('[PASS]' if aliased.dependents['e'] == ['d', 'f'] else '[FAIL]')
+    print('===========================================================')
+    # every mod of a layer depends on every mod of the next one
+    layers = [[f'l{x}m{y}' for y in range(4)] for x in range(8)]
+    dense: Dict[str, Mod] = {}
+    for depth, layer in enumerate(layers):
+        for modid in layer:
+            mod = Mod(cast(ModPack, None))
+            mod.modid = modid
+            below = layers[depth + 1] if depth + 1 < len(layers) else []
+            mod.dependencies = [ModDependency(x, True, '*') for x in below]
+            dense[modid] = mod
+    chains, truncated = DependencyGraph(dense).reverse_dependents('l7m0', 10)
+    print(len(chains), truncated)
+    print('[PASS]' if len(chains) == 10 and truncated else '[FAIL]')
+
+
+if __name__ == '__main__':
+    test()
@@ -0,0 +1,112 @@
+
+from attrs import define, Factory
+import toml
+
+from typing import Any, Dict, List, Optional, Tuple
+import os
+
+from output import SEVERITIES
+from version import VersionRange, BadVersionString
+
+
+# shipped next to the scripts
+BUNDLED_DB = os.path.join(os.path.dirname(__file__), 'incompat.toml')
+
+
+class IncompatError(ValueError):
+    ...
+
+
+@define
+class Incompatibility:
+    mods:       Tuple[str, str]
+    reason:     str
+    # the file the entry is from
+    source:     str
+    # only these versions of the mod are affected, by modid
+    versions:   Dict[str, List[VersionRange]] = Factory(dict)
+    severity:   str = 'warning'
+
+    # the same pair in either order
+    @property
+    def key(self) -> Tuple[str, str]:
+        first, second = sorted(self.mods)
+        return first, second
+
+
+def parse_entry(entry: Any, source: str) -> Incompatibility:
+    if not isinstance(entry, dict):
+        raise IncompatError(f'{source}: expected a table, got {entry!r}')
+
+    mods = entry.get('mods', None)
+    if not isinstance(mods, list) or len(mods) != 2:
+        raise IncompatError(f'{source}: `mods` needs to be two modids')
+    if not all([isinstance(x, str) and x for x in mods]):
+        raise IncompatError(f'{source}: `mods` needs to be two modids')
+    reason = entry.get('reason', None)
+    if not isinstance(reason, str) or reason == '':
+        raise IncompatError(f'{source}: {mods} is missing a `reason`')
+
+    severity = entry.get('severity', 'warning')
+    if severity not in SEVERITIES:
+        raise IncompatError(
+            f"{source}: {mods} has unknown severity '{severity}', "
+            f"expected one of {', '.join(SEVERITIES)}"
+        )
+
+    incompatibility = Incompatibility(
+        (mods[0], mods[1]),
+        reason,
+        source,
+        severity=severity
+    )
+    versions = entry.get('versions', {})
+    if not isinstance(versions, dict):
+        raise IncompatError(f'{source}: {mods} `versions` has to be a table')
+    for modid, version_range in versions.items():
+        if modid not in mods:
+            raise IncompatError(
+                f"{source}: {mods} has versions for '{modid}', which isn't "
+                f"one of its mods"
+            )
+        try:
+            incompatibility.versions[modid] = VersionRange.fromString(
+                str(version_range)
+            )
+        except BadVersionString as e:
+            raise IncompatError(
+                f"{source}: {mods} has invalid version range "
+                f"'{version_range}' for '{modid}': {e}"
+            ) from e
+
+    return incompatibility
+
+
+def parse_incompat(text: str, source: str) -> List[Incompatibility]:
+    try:
+        data: Dict[str, Any] = toml.loads(text)
+    except toml.TomlDecodeError as e:
+        raise IncompatError(f'{source}: {e}') from e
+    entries = data.get('incompatible', [])
+    if not isinstance(entries, list):
+        raise IncompatError(f'{source}: `incompatible` has to be a list')
+    return [parse_entry(x, source) for x in entries]
+
+
+def read_incompat(path: str) -> List[Incompatibility]:
+    try:
+        with open(path, 'r') as file:
+            text = file.read()
+    except OSError as e:
+        raise IncompatError(f"failed to read '{path}': {e}") from e
+    return parse_incompat(text, path)
+
+
+# the bundled database, with the entries of `path` replacing the ones for
+# the same pair of mods
+def load_incompat(path: Optional[str] = None) -> List[Incompatibility]:
+    entries: Dict[Tuple[str, str], Incompatibility] = {}
+    for source in [BUNDLED_DB] + ([path] if path else []):
+        for entry in read_incompat(source):
+            entries[entry.key] = entry
+    return list(entries.values())
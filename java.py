@@ -0,0 +1,46 @@
+
+from typing import Optional
+from zipfile import ZipFile
+
+from filesystem import DirectoryZip
+
+
+CLASS_MAGIC = b'\xca\xfe\xba\xbe'
+# class file major version 45 is java 1.1, 52 is java 8, 65 is java 21
+MAJOR_VERSION_OFFSET = 44
+
+
+# the major version of a class file, from its first 8 bytes
+def class_major_version(header: bytes) -> Optional[int]:
+    if len(header) < 8 or header[:4] != CLASS_MAGIC:
+        return None
+    return int.from_bytes(header[6:8], 'big')
+
+
+def java_version_of(major_version: int) -> int:
+    return max(major_version - MAJOR_VERSION_OFFSET, 1)
+
+
+# the lowest java version able to load every class in the jar, unknown
+# without any. the manifest only has the jdk that built it, which can be
+# newer than what the classes target
+def jar_java_version(jar: DirectoryZip) -> Optional[int]:
+    zip_file = jar._zip
+    if not isinstance(zip_file, ZipFile):
+        return None
+
+    highest: Optional[int] = None
+    for item in zip_file.infolist():
+        if item.is_dir() or not item.filename.endswith('.class'):
+            continue
+        # multi-release jars only use these on newer javas
+        if item.filename.startswith('META-INF/versions/'):
+            continue
+        with zip_file.open(item, 'r') as file:
+            major_version = class_major_version(file.read(8))
+        if major_version is not None:
+            highest = max(highest or 0, major_version)
+
+    if highest is not None:
+        return java_version_of(highest)
+    return None
@@ -0,0 +1,140 @@
+
+from typing import Any, Dict, List
+import json
+import os
+
+from filesystem import FileBase, FileReal, DirectoryReal
+from mod_info import ModPack
+from output import LockReport, LockFileReport, VerifyReport, LockChangeReport
+
+
+LOCK_FILE = 'mc-packer.lock'
+# bump whenever the layout of the lock file changes
+LOCK_VERSION = 1
+HASH_ALGORITHM = 'sha256'
+
+
+class LockError(Exception):
+    ...
+
+
+def lock_path(pack: ModPack) -> str:
+    return os.path.join(pack.directory.full_path, LOCK_FILE)
+
+
+# the version of the top-level mod in every jar
+def jar_versions(pack: ModPack) -> Dict[str, str]:
+    versions: Dict[str, str] = {}
+    for mod in pack.all_mods:
+        if not mod.nested:
+            name = os.path.basename(mod.jar_file)
+            versions.setdefault(name, str(mod._version))
+    return versions
+
+
+# every jar the profile loads, hashed
+def lock_files(pack: ModPack) -> List[LockFileReport]:
+    mod_dir = DirectoryReal(pack.directory, 'mods')
+    extensions = ('.jar', '.jar.disabled') if pack.include_disabled else '.jar'
+    names = sorted([
+        x.name for x in mod_dir.list()
+        if issubclass(type(x), FileBase) and x.name.endswith(extensions)
+    ])
+
+    versions = jar_versions(pack)
+    return [
+        LockFileReport(
+            name,
+            FileReal(mod_dir, name).hash(HASH_ALGORITHM),
+            versions.get(name, None)
+        )
+        for name in names
+    ]
+
+
+def write_lock(pack: ModPack) -> LockReport:
+    report = LockReport(lock_path(pack), lock_files(pack))
+    try:
+        with open(report.output, 'w') as file:
+            json.dump(
+                {
+                    'version': LOCK_VERSION,
+                    'algorithm': HASH_ALGORITHM,
+                    'files': [
+                        {
+                            'file': x.file,
+                            'hash': x.hash,
+                            'version': x.version,
+                        }
+                        for x in report.files
+                    ],
+                },
+                file,
+                indent=2
+            )
+            file.write('\n')
+    except OSError as e:
+        raise LockError(f"lock: failed to write '{report.output}': {e}")
+    return report
+
+
+def read_lock(path: str) -> List[LockFileReport]:
+    try:
+        with open(path, 'r') as file:
+            data: Dict[str, Any] = json.load(file)
+    except FileNotFoundError:
+        raise LockError(
+            f"verify: '{path}' doesn't exist, create it with `lock`"
+        )
+    except (OSError, ValueError) as e:
+        raise LockError(f"verify: failed to read '{path}': {e}")
+
+    try:
+        if data['version'] != LOCK_VERSION:
+            raise LockError(
+                f"verify: '{path}' has lock version {data['version']}, "
+                f"expected {LOCK_VERSION}"
+            )
+        if data['algorithm'] != HASH_ALGORITHM:
+            raise LockError(
+                f"verify: '{path}' uses {data['algorithm']} hashes, "
+                f"expected {HASH_ALGORITHM}"
+            )
+        return [
+            LockFileReport(
+                str(x['file']),
+                str(x['hash']),
+                x.get('version', None)
+            )
+            for x in data['files']
+        ]
+    except (KeyError, TypeError, AttributeError) as e:
+        raise LockError(f"verify: '{path}' is malformed: {e}")
+
+
+# files added, removed and changed since the lock was written
+def verify_lock(pack: ModPack) -> VerifyReport:
+    report = VerifyReport(lock_path(pack))
+    locked = {x.file: x for x in read_lock(report.lock)}
+    current = {x.file: x for x in lock_files(pack)}
+
+    for name in sorted(set(locked.keys()) | set(current.keys())):
+        if name not in locked:
+            report.added.append(current[name])
+        elif name not in current:
+            report.removed.append(locked[name])
+        elif locked[name].hash != current[name].hash:
+            report.changed.append(LockChangeReport(
+                name,
+                locked[name].version,
+                current[name].version
+            ))
+        else:
+            report.unchanged += 1
+
+    report.passed = (
+        len(report.added) == 0 and
+        len(report.removed) == 0 and
+        len(report.changed) == 0
+    )
+    return report
@@ -1,9 +1,42 @@
 
+from tqdm import tqdm
+
 # from typing import
+import logging
+import sys
 
 from filesystem import FileReal, DirectoryBase
 
 
+# below `logging.DEBUG`, for the steps of every single check
+TRACE = 5
+logging.addLevelName(TRACE, 'TRACE')
+
+# by the number of `-v`
+VERBOSITY_LEVELS = [logging.WARNING, logging.INFO, logging.DEBUG, TRACE]
+
+
+# goes through tqdm so log lines don't break up the progress bar
+class TqdmHandler(logging.Handler):
+    def emit(self, record: logging.LogRecord) -> None:
+        try:
+            tqdm.write(self.format(record), file=sys.stderr)
+        except Exception:
+            self.handleError(record)
+
+
+def setup_logging(verbosity: int) -> None:
+    handler = TqdmHandler()
+    handler.setFormatter(
+        logging.Formatter('[%(levelname)s %(name)s] %(message)s')
+    )
+    root = logging.getLogger()
+    root.addHandler(handler)
+    root.setLevel(
+        VERBOSITY_LEVELS[min(verbosity, len(VERBOSITY_LEVELS) - 1)]
+    )
+
+
 class Log(FileReal):
     def identifyError(self, error: str) -> bool:
         return False
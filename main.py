@@ -1,69 +1,323 @@
 
-from zipfile import ZipFile
-from typing import cast
+from tqdm import tqdm
+
+from typing import Any, Callable, Dict, List, Tuple
 import argparse
+import logging
+import sys
 import os
 
-from filesystem import DirectoryReal, DirectoryZip, FileZip
-from version import VersionRange, Version, VersionRangePart
-from mod_info import ModPack, Mod
+from filesystem import DirectoryReal
+from version import Version
+from mod_info import ModPack
+from metadata import LOADER_MODIDS
+from diagnostics import find_error, find_log_errors
+from overrides import (
+    parse_overrides, parse_override_file, parse_lies, is_pattern,
+    OverrideError
+)
+from output import (
+    emit, use_color, FORMATS, COLOR_MODES, ProfileReport, ProfilesReport
+)
+from clean import clean
+from cache import ScanCache
+from mrpack import export_mrpack, ExportError
+from server import export_server
+from remote import ModrinthResolver
+from curseforge import has_manifest, check_manifest
+from diff import diff_packs
+from dot import write_dot
+from lock import write_lock, verify_lock, LockError
+from watch import watch
+from incompat import load_incompat, Incompatibility, IncompatError
+from detect import detect_profile_versions
+from logger import setup_logging
 
 
-def main(args: argparse.Namespace):
-    if not os.path.isdir(args.instance):
-        if not os.path.isdir(os.path.join(os.getcwd(), args.instance)):
-            print(f"invalid instance directory '{args.instance}'")
+log = logging.getLogger('main')
+
+
+def find_instance(instance: str) -> str:
+    if not os.path.isdir(instance):
+        if not os.path.isdir(os.path.join(os.getcwd(), instance)):
+            print(f"invalid instance directory '{instance}'")
             exit(255)
         else:
-            args.instance = os.path.join(os.getcwd(), args.instance)
+            instance = os.path.join(os.getcwd(), instance)
+    return instance
 
-    pack = ModPack(DirectoryReal(None, args.instance))
 
-    print('LOADING PACK')
-    if not pack.load():
-        return
-    if args.versions:
-        for modid, version in [
-                    vers.split('=') for vers in args.versions.split(',')
-                ]:
-            if modid not in pack.mods:
-                mod = Mod(pack)
-                mod._version = Version.fromString(version)
-                mod.filename = '[no file]'
-                mod.name = modid
-                mod.modid = modid
-                pack.mods[modid] = mod
-            else:
-                pack.mods[modid]._version = Version.fromString(version)
+def main(args: argparse.Namespace):
+    # keep stdout clean for machine-readable output
+    def status(message: str) -> None:
+        if args.quiet:
+            return
+        # written above the progress bar instead of through it
+        tqdm.write(
+            message,
+            file=sys.stderr if args.format == 'json' else sys.stdout
+        )
 
-    if args.lies:
-        for modid in args.lies.split(','):
-            if modid not in pack.mods:
-                continue
-            mod = pack.mods[modid]
-            for dep in mod.dependencies:
-                if dep.modid in pack.mods:
-                    range_part = VersionRangePart(
-                        pack.mods[dep.modid]._version,
-                        True
+    instances = [find_instance(x) for x in args.instance]
+    use_color(args.color, args.format)
+
+    # inline overrides take precedence over the ones from the file
+    overrides: Dict[str, Version] = {}
+    sources: Dict[str, str] = {}
+    try:
+        if args.override_file:
+            with open(args.override_file, 'r') as file:
+                content = file.read()
+            for modid, version in parse_override_file(
+                        content,
+                        args.override_file
+                    ).items():
+                overrides[modid] = version
+                sources[modid] = args.override_file
+        if args.versions:
+            for modid, version in parse_overrides(args.versions).items():
+                if modid in sources:
+                    log.debug(
+                        f"override: '{modid}' from --override-versions "
+                        f"replaces {overrides[modid]} from {sources[modid]}"
                     )
-                    dep.version_reqs = [VersionRange(range_part, range_part)]
+                overrides[modid] = version
+                sources[modid] = '--override-versions'
+    except OSError as e:
+        print(f"failed to read override file '{args.override_file}': {e}")
+        exit(255)
+    except OverrideError as e:
+        print(e)
+        exit(255)
+
+    for modid, version in sorted(overrides.items()):
+        log.debug(f"override: {modid}={version} (from {sources[modid]})")
+
+    try:
+        incompatibilities = load_incompat(args.incompat_db)
+    except IncompatError as e:
+        print(f'incompat-db: {e}')
+        exit(255)
+
+    if args.jobs is not None and args.jobs < 1:
+        print(f"invalid job count '{args.jobs}', expected at least 1")
+        exit(255)
 
-    print('VALIDATING PACK')
-    validation = pack.validateVersions(verbose=(args.subcommand == "validate"))
-    if args.subcommand == "validate":
-        if validation:
-            print(' -> [PASS]')
+    if args.subcommand == 'diff':
+        if len(instances) != 2:
+            print('diff: expected exactly two instance directories')
+            exit(255)
+        old, new = [
+            load_pack(args, x, overrides, status) for x in instances
+        ]
+        emit(args.format, diff_packs(old, new))
         return
 
-    if args.subcommand == 'why-depends':
-        pack.why_depends(args.modid, args.why_errors)
+    single = ['export', 'export-server', 'graph', 'watch']
+    if args.subcommand in single and len(instances) > 1:
+        print(f'{args.subcommand}: expected a single instance directory')
+        exit(255)
+
+    if args.subcommand == 'watch':
+        def validate() -> None:
+            report, _ = run(
+                args, instances[0], overrides, incompatibilities, status
+            )
+            emit(args.format, report)
+            status('WATCHING FOR CHANGES (ctrl-c to stop)')
+
+        watch(instances[0], validate)
         return
 
-    print("TESTING PACK")
-    pack.identifyBrokenMods(args.error)
+    results: List[ProfileReport] = []
+    exit_code = 0
+    for instance in instances:
+        if len(instances) > 1:
+            status(f'PROFILE {instance}')
+        report, code = run(
+            args, instance, overrides, incompatibilities, status
+        )
+        results.append(ProfileReport(instance, report))
+        exit_code = max(exit_code, code)
+
+    if len(results) == 1:
+        emit(args.format, results[0].report)
+    else:
+        emit(args.format, ProfilesReport(results))
+    exit(exit_code)
+
+
+def load_pack(
+            args: argparse.Namespace,
+            instance: str,
+            overrides: Dict[str, Version],
+            status: Callable[[str], None]
+        ) -> ModPack:
+    # versions from the profile, unless overridden
+    detected = detect_profile_versions(instance)
+    if detected is not None:
+        versions = detected.overrides()
+        # an overridden loader replaces the detected one, not joins it
+        if any([x in LOADER_MODIDS for x in overrides.keys()]):
+            versions = {
+                modid: version for modid, version in versions.items()
+                if modid not in LOADER_MODIDS
+            }
+        for modid, version in versions.items():
+            if modid in overrides:
+                log.debug(
+                    f"override: {modid}={overrides[modid]} replaces the "
+                    f"detected {version} from {detected.source}"
+                )
+        overrides = {**versions, **overrides}
+
+    loader = None
+    for modid in overrides.keys():
+        if modid in LOADER_MODIDS:
+            loader = LOADER_MODIDS[modid]
+
+    directory = DirectoryReal(None, instance)
+    pack = ModPack(directory, loader, args.include_disabled)
+    cache = None if args.no_cache else ScanCache.open(pack.directory)
+
+    status('LOADING PACK')
+    # the bar goes to stderr, only draw it for people watching
+    progress = (
+        not args.quiet
+        and args.format != 'json'
+        and sys.stderr.isatty()
+    )
+    pack.load(args.jobs, cache, progress)
+    if cache is not None:
+        cache.save()
+    for modid, version in overrides.items():
+        pack.override(modid, version)
+
+    if args.lies:
+        for pattern, modids in pack.lie_depends(parse_lies(args.lies)).items():
+            if not is_pattern(pattern):
+                if len(modids) == 0:
+                    status(f"lie-depends: modid '{pattern}' is not installed")
+            elif len(modids) == 0:
+                status(f"lie-depends: '{pattern}' matched no installed mods")
+            else:
+                status(
+                    f"lie-depends: '{pattern}' matched {len(modids)} mods: "
+                    f"{', '.join(modids)}"
+                )
+
+    return pack
+
+
+# the report of the subcommand for a single profile, and its exit code
+def run(
+            args: argparse.Namespace,
+            instance: str,
+            overrides: Dict[str, Version],
+            incompatibilities: List[Incompatibility],
+            status: Callable[[str], None]
+        ) -> Tuple[Any, int]:
+    directory = DirectoryReal(None, instance)
+    # exported curseforge packs only list their mods in `manifest.json`
+    if has_manifest(directory) and not directory.has('mods'):
+        status('VALIDATING CURSEFORGE MANIFEST')
+        manifest = check_manifest(directory)
+        return manifest, 0 if manifest.valid else 2
+
+    pack = load_pack(args, instance, overrides, status)
+
+    if args.subcommand == 'clean':
+        return clean(pack, args.confirm and not args.dry_run), 0
+
+    if args.subcommand == 'export':
+        name = args.name or os.path.basename(os.path.normpath(instance))
+        try:
+            report = export_mrpack(pack, args.mrpack, name, args.pack_version)
+        except ExportError as e:
+            print(e)
+            exit(255)
+        return report, 0
+
+    if args.subcommand == 'export-server':
+        try:
+            server = export_server(pack, args.out, args.symlink)
+        except ExportError as e:
+            print(e)
+            exit(255)
+        return server, 2 if len(server.client_dependencies) > 0 else 0
+
+    if args.subcommand == 'graph':
+        try:
+            with open(args.dot, 'w') as file:
+                return write_dot(pack, file, args.dot), 0
+        except OSError as e:
+            print(f"graph: failed to write '{args.dot}': {e}")
+            exit(255)
+
+    if args.subcommand in ['lock', 'verify']:
+        try:
+            if args.subcommand == 'lock':
+                return write_lock(pack), 0
+            verification = verify_lock(pack)
+        except LockError as e:
+            print(e)
+            exit(255)
+        return verification, 0 if verification.passed else 2
+
+    status('VALIDATING PACK')
+    validation = pack.validateVersions(
+        args.side,
+        args.java,
+        args.mixins,
+        args.orphans,
+        args.access,
+        args.recommendations,
+        incompatibilities,
+        args.install_order
+    )
+    if args.subcommand in ['validate', 'watch', None]:
+        return validation, validation.exit_code(args.error_on_warning)
+
     if args.subcommand == 'find-error':
-        return
+        # a path to a log or crash report is read as a whole
+        path = args.error_file
+        if path is None and args.error and os.path.isfile(args.error):
+            path = args.error
+        if path is not None:
+            try:
+                with open(path, 'r', errors='replace') as file:
+                    text = file.read()
+            except OSError as e:
+                print(f"find-error: failed to read '{path}': {e}")
+                exit(255)
+            return find_log_errors(pack, text, path), 0
+        if not args.error:
+            print('find-error: expected an error message or --file')
+            exit(255)
+        return find_error(pack, args.error), 0
+
+    if args.subcommand == 'mod-info':
+        return pack.mod_info(args.modid, args.deps), 0
+
+    if args.subcommand == 'why-depends':
+        resolver = None
+        if args.online:
+            minecraft = pack.mods.get('minecraft', None)
+            # without an overridden loader, go by what most mods are for
+            loaders = [x.loader for x in pack.mods.values() if x.loader]
+            loader = pack.loader or max(
+                set(loaders), key=loaders.count, default=None
+            )
+            resolver = ModrinthResolver(
+                str(minecraft._version) if minecraft is not None else None,
+                loader
+            )
+        return pack.why_depends(args.modid, args.why_errors, resolver), 0
+
+    if args.subcommand == 'load-order':
+        return pack.load_order(), 0
+
+    raise ValueError(f'unknown subcommand {args.subcommand}')
 
 
 if __name__ == '__main__':
@@ -72,18 +326,134 @@ if __name__ == '__main__':
         '--override-versions',
         dest='versions',
         type=str,
-        help='<modid>=<version>[,<modid>=<version>[,...]]'
+        help='<modid>=<version>[,<modid>=<version>[,...]]. a version of `*` '
+             'accepts any version, eg: `forge=*`'
+    )
+    parser.add_argument(
+        '--override-file',
+        dest='override_file',
+        type=str,
+        help='a file of `<modid>=<version>` lines, `#` starts a comment. '
+             '--override-versions takes precedence over it'
     )
     parser.add_argument(
         '--lie-depends',
         dest='lies',
         help='lie to the provided mods so they think requirements are met. '
-             'eg: `<modid>[,<modid>[,...]]`'
+             'eg: `<modid>[,<modid>[,...]]`, modids can be patterns like '
+             '`create_*`'
     )
     parser.add_argument(
-        'instance',
+        '--incompat-db',
+        dest='incompat_db',
         type=str,
-        help='the folder of your minecraft profile'
+        help='a toml file of incompatible pairs of mods, added to the '
+             'bundled `incompat.toml` and replacing its entries for the same '
+             'mods'
+    )
+    parser.add_argument(
+        '--format',
+        dest='format',
+        choices=FORMATS,
+        default='text',
+        help='the output format of the subcommand'
+    )
+    parser.add_argument(
+        '--color',
+        dest='color',
+        choices=COLOR_MODES,
+        default='auto',
+        help='color the text output, `auto` does for terminals unless '
+             'NO_COLOR is set (default: auto)'
+    )
+    parser.add_argument(
+        '--jobs',
+        dest='jobs',
+        type=int,
+        help='the number of processes scanning jars (default: cpu count)'
+    )
+    parser.add_argument(
+        '--side',
+        dest='side',
+        choices=['client', 'server'],
+        help='also check that every mod can run on the client or a '
+             'dedicated server'
+    )
+    parser.add_argument(
+        '--java',
+        dest='java',
+        type=int,
+        help='also check that every mod can run on this java version, '
+             'eg: `17`'
+    )
+    parser.add_argument(
+        '--mixins',
+        dest='mixins',
+        action='store_true',
+        help='also report classes changed by mixins of several mods'
+    )
+    parser.add_argument(
+        '--access',
+        dest='access',
+        action='store_true',
+        help='also report game members whose access transformers or '
+             'wideners of several mods change'
+    )
+    parser.add_argument(
+        '--report-orphans',
+        dest='orphans',
+        action='store_true',
+        help='also report libraries that nothing in the pack depends on, '
+             'either marked as such or without content or mixins'
+    )
+    parser.add_argument(
+        '--show-recommendations',
+        dest='recommendations',
+        action='store_true',
+        help='also list the recommended and suggested mods that are '
+             'missing, without affecting the exit code'
+    )
+    parser.add_argument(
+        '--suggest-install-order',
+        dest='install_order',
+        action='store_true',
+        help='also list the missing required mods in an order to install '
+             'them in, the ones others require first'
+    )
+    parser.add_argument(
+        '--include-disabled',
+        dest='include_disabled',
+        action='store_true',
+        help='treat `.jar.disabled` mods as if they were enabled'
+    )
+    parser.add_argument(
+        '--no-cache',
+        dest='no_cache',
+        action='store_true',
+        help='rescan every jar instead of reusing the results cached in the '
+             'profile'
+    )
+    parser.add_argument(
+        '--quiet',
+        '-q',
+        dest='quiet',
+        action='store_true',
+        help='only print the report, without progress or status messages'
+    )
+    parser.add_argument(
+        '--verbose',
+        '-v',
+        dest='verbose',
+        action='count',
+        default=0,
+        help='log what is being done to stderr, -v for info, -vv for debug '
+             'and -vvv for trace'
+    )
+    parser.add_argument(
+        '--debug',
+        dest='debug',
+        action='store_true',
+        help='the same as -vv'
     )
     subparsers = parser.add_subparsers(
         dest='subcommand',
@@ -92,18 +462,48 @@ if __name__ == '__main__':
 
     validate_parser = subparsers.add_parser(
         'validate',
-        help='validate dependendies in the pack (10-second runtime)'
+        help='validate dependendies in the pack, exiting with 1 on warnings '
+             'and 2 on errors (10-second runtime)'
+    )
+    validate_parser.add_argument(
+        '--error-on-warning',
+        dest='error_on_warning',
+        action='store_true',
+        help='exit with 2 on warnings as well'
     )
     find_error_parser = subparsers.add_parser(
         'find-error',
-        help='intelligently disable mods until the mod causing the '
-             'provided error is found, then re-enable the other mods '
-             '(very long runtime)'
+        help='explain the provided error, or every error of a log, and which '
+             'installed mods it mentions (10-second runtime)'
         )
     find_error_parser.add_argument(
         'error',
         type=str,
-        help='the error to solve for'
+        nargs='?',
+        help='the error to solve for, or the path of a log to scan'
+    )
+    find_error_parser.add_argument(
+        '--file',
+        dest='error_file',
+        type=str,
+        help='a log or crash report to scan for every error, eg: '
+             '`logs/latest.log`'
+    )
+    mod_info_parser = subparsers.add_parser(
+        'mod-info',
+        help='show information about the provided modid, or list all mods '
+             '(10-second runtime)'
+    )
+    mod_info_parser.add_argument(
+        '--deps',
+        action='store_true',
+        help='also show the dependencies of the mod, and theirs in turn'
+    )
+    mod_info_parser.add_argument(
+        'modid',
+        type=str,
+        nargs='?',
+        help="the modid to show"
     )
     why_depends_parser = subparsers.add_parser(
         'why-depends',
@@ -111,15 +511,117 @@ if __name__ == '__main__':
     )
     why_depends_parser.add_argument(
         '--errors',
+        '--errors-only',
         action='store_true',
-        help='only print version mismatches',
+        help='only print dependency chains with unsatisfied requirements',
         dest='why_errors'
     )
+    why_depends_parser.add_argument(
+        '--online',
+        action='store_true',
+        help='with --errors, look up compatible versions of missing '
+             'dependencies on modrinth'
+    )
     why_depends_parser.add_argument(
         'modid',
         type=str,
         help="the modid to check"
     )
+    load_order_parser = subparsers.add_parser(
+        'load-order',
+        help='show the order mods load in, following the ordering of their '
+             'dependencies (10-second runtime)'
+    )
+    export_parser = subparsers.add_parser(
+        'export',
+        help='export the profile as a modpack (10-second runtime)'
+    )
+    export_parser.add_argument(
+        '--mrpack',
+        required=True,
+        help='the modrinth `.mrpack` file to write'
+    )
+    export_parser.add_argument(
+        '--name',
+        help='the name of the pack (default: the profile folder name)'
+    )
+    export_parser.add_argument(
+        '--pack-version',
+        dest='pack_version',
+        default='1.0.0',
+        help='the version of the pack (default: 1.0.0)'
+    )
+    export_server_parser = subparsers.add_parser(
+        'export-server',
+        help='copy the jars a dedicated server needs to a folder, leaving out '
+             'client-only mods, exiting with 2 if a server mod requires one '
+             '(10-second runtime)'
+    )
+    export_server_parser.add_argument(
+        '--out',
+        required=True,
+        help='the folder to put the jars in, eg: `mods/` of the server'
+    )
+    export_server_parser.add_argument(
+        '--symlink',
+        action='store_true',
+        help='link to the jars of the profile instead of copying them'
+    )
+    graph_parser = subparsers.add_parser(
+        'graph',
+        help='write the dependency graph of the pack (10-second runtime)'
+    )
+    graph_parser.add_argument(
+        '--dot',
+        required=True,
+        help='the graphviz `.dot` file to write'
+    )
+    diff_parser = subparsers.add_parser(
+        'diff',
+        help='show the mods added, removed and changed between two profiles '
+             '(10-second runtime)'
+    )
+    lock_parser = subparsers.add_parser(
+        'lock',
+        help='write the hashes of every jar in the profile to '
+             '`mc-packer.lock` (10-second runtime)'
+    )
+    verify_parser = subparsers.add_parser(
+        'verify',
+        help='check that the jars in the profile are the ones in '
+             '`mc-packer.lock`, exiting with 2 if not (10-second runtime)'
+    )
+    subparsers.add_parser(
+        'watch',
+        help='validate the pack again whenever jars in `mods/` are added, '
+             'removed or renamed, until ctrl-c'
+    )
+    clean_parser = subparsers.add_parser(
+        'clean',
+        help='remove config files that belong to mods which are not '
+             'installed (10-second runtime)'
+    )
+    clean_parser.add_argument(
+        '--dry-run',
+        action='store_true',
+        help='only list the config files that would be removed (default)'
+    )
+    clean_parser.add_argument(
+        '--confirm',
+        action='store_true',
+        help='actually remove the orphaned config files'
+    )
+
+    # after the arguments of each subcommand, so optional positionals like
+    # the error of `find-error` come first
+    for subparser in subparsers.choices.values():
+        subparser.add_argument(
+            'instance',
+            type=str,
+            nargs='+',
+            help='the folders of your minecraft profiles'
+        )
 
     args = parser.parse_args()
+    setup_logging(max(args.verbose, 2 if args.debug else 0))
     main(args)
@@ -0,0 +1,953 @@
+
+from attrs import define, Factory
+import toml
+
+from typing import cast, List, Dict, Union, Any, Type, Optional, Set, Tuple
+from zipfile import ZipFile, BadZipFile
+import hashlib
+import logging
+import json
+import io
+import os
+import re
+
+from filesystem import DirectoryReal, DirectoryZip, FileZip
+from java import jar_java_version
+from mixins import Mixin, find_configs, read_mixins, find_missing_refmaps
+from access import Access, read_access, FORGE_TRANSFORMER
+from logger import TRACE
+
+
+log = logging.getLogger(__name__)
+
+
+class MetaError(Exception):
+    ...
+
+
+# manifest attributes a placeholder is read from, the first one present
+# wins, or a fixed value
+MANIFEST_MAPPING: Dict[str, Union[str, List[str]]] = {
+    'file.jarVersion': [
+        'Implementation-Version',
+        'Specification-Version',
+    ],
+    'file.jarImplementationVersion': ['Implementation-Version'],
+    # temp solution until I learn where to actually get these from
+    'forge_version_range': '*',
+    'minecraft_version_range': '*',
+}
+PLACEHOLDER = re.compile(r'\${([^}]+)}')
+
+
+def has_placeholder(text: str) -> bool:
+    return PLACEHOLDER.search(text) is not None
+
+
+def parse_manifest(manifest: str) -> Dict[str, str]:
+    result: Dict[str, str] = {}
+
+    manifest = manifest.replace('\r\n', '\n')
+    while '\n\n' in manifest:
+        manifest = manifest.replace('\n\n', '\n')
+
+    for line in manifest.split('\n'):
+        parts = line.split(':')
+        if len(parts) == 2:
+            result[parts[0].strip()] = parts[1].strip()
+
+    return result
+
+
+def read_manifest(jar: DirectoryZip) -> Dict[str, str]:
+    if not jar.has("META-INF/MANIFEST.MF"):
+        return {}
+    return parse_manifest(
+        FileZip("META-INF/MANIFEST.MF", jar).read().decode(errors='ignore')
+    )
+
+
+# the value of a `${<var_name>}` placeholder, `global.<name>` ones are read
+# from a `<name>` manifest attribute
+def placeholder_value(field: str, manifest: Dict[str, str]) -> Optional[str]:
+    map = MANIFEST_MAPPING.get(field, None)
+    if map is None and field.startswith('global.'):
+        map = [field.removeprefix('global.')]
+
+    if isinstance(map, str):
+        return map
+    for key in map or []:
+        if manifest.get(key, ''):
+            return manifest[key]
+    return None
+
+
+# replaces the placeholders in the field, the ones without a value are left
+# as they are
+def process_external_field(
+            field_raw: str,
+            manifest: Dict[str, str],
+            source: str = ''
+        ) -> str:
+    def replace(match: re.Match) -> str:
+        value = placeholder_value(match.group(1), manifest)
+        if value is None:
+            log.debug(f"'{source}': no value for placeholder {match.group()}")
+            return match.group()
+        return value
+
+    return PLACEHOLDER.sub(replace, field_raw)
+
+
+@define
+class Dependency:
+    modid:          str
+    mandatory:      bool
+    version_range:  str
+    ordering:       str = 'NONE'
+    side:           str = 'BOTH'
+    # one of `depends`, `recommends`, `suggests`, `conflicts` or `breaks`
+    kind:           str = 'depends'
+
+
+@define
+class ModInfo:
+    file:           str
+    modid:          str
+    version:        str
+    name:           str
+    loader:         str
+    dependencies:   List[Dependency] = Factory(list)
+    # `client`, `server` or `*`
+    environment:    str = '*'
+    # the top-level jar in the mods folder containing this mod
+    jar_file:       str = ''
+    # the `file` of the mod this one is nested in
+    nested_in:      Optional[str] = None
+    # the lowest java version able to load the mod's classes
+    java_version:   Optional[int] = None
+    mixin_configs:  List[str] = Factory(list)
+    mixins:         List[Mixin] = Factory(list)
+    # refmaps named by the mixin configs but not in the jar, by config
+    missing_refmaps: Dict[str, str] = Factory(dict)
+    # access transformers and wideners, and the members they change
+    access_files:   List[str] = Factory(list)
+    access:         List[Access] = Factory(list)
+    # other modids this mod satisfies dependencies on
+    provides:       List[str] = Factory(list)
+    # marked as a library in its metadata, eg: a mod menu `library` badge
+    library:        bool = False
+    # ships blocks, items, recipes or the like, see `has_content`
+    content:        bool = True
+
+    METADATA_PATH = ""
+    LOADER = ""
+
+    @classmethod
+    def from_jar(cls, path: str) -> 'ModInfo':
+        try:
+            with ZipFile(path, 'r') as jar:
+                parent = DirectoryReal(None, os.path.dirname(path))
+                return cls.from_zip(
+                    DirectoryZip(parent, os.path.basename(path), jar)
+                )
+        except (OSError, BadZipFile) as e:
+            raise MetaError(f"failed to open jar '{path}': {e}") from e
+
+    @classmethod
+    def from_zip(cls, jar: DirectoryZip) -> 'ModInfo':
+        raise NotImplementedError
+
+
+@define
+class ForgeMetadata(ModInfo):
+    METADATA_PATH = "META-INF/mods.toml"
+    LOADER = "forge"
+
+    # neoforge replaced `mandatory` with `type`
+    DEPENDENCY_TYPES = {
+        'required':     ('depends', True),
+        'optional':     ('depends', False),
+        'discouraged':  ('conflicts', False),
+        'incompatible': ('breaks', False),
+    }
+    # the `type` of dependencies without one, forge falls back to
+    # `mandatory` instead
+    DEFAULT_TYPE = None
+
+    @classmethod
+    def from_zip(cls, jar: DirectoryZip) -> 'ForgeMetadata':
+        if not jar.has(cls.METADATA_PATH):
+            raise MetaError(f"'{jar.full_path}' has no {cls.METADATA_PATH}")
+
+        try:
+            toml_data: Dict[str, Any] = toml.loads(
+                FileZip(cls.METADATA_PATH, jar).read().decode()
+            )
+        except (toml.TomlDecodeError, UnicodeDecodeError) as e:
+            raise MetaError(
+                f"invalid {cls.METADATA_PATH} in '{jar.full_path}': {e}"
+            ) from e
+
+        mods = toml_data.get('mods', [])
+        if len(mods) == 0:
+            raise MetaError(
+                f"{cls.METADATA_PATH} in '{jar.full_path}' declares no mods"
+            )
+
+        manifest = read_manifest(jar)
+        mod = mods[0]
+
+        def resolve(field: str) -> str:
+            return process_external_field(field, manifest, jar.full_path)
+
+        try:
+            modid = resolve(mod['modId'])
+            name = mod.get('displayName', modid)
+            instance = cls(
+                jar.full_path,
+                modid,
+                resolve(mod['version']),
+                resolve(name),
+                cls.LOADER
+            )
+        except KeyError as e:
+            raise MetaError(
+                f"{cls.METADATA_PATH} in '{jar.full_path}' is missing {e}"
+            ) from e
+
+        deps = toml_data.get('dependencies', {}).get(instance.modid, [])
+        for dependency in deps:
+            if dependency.get('modId', None) is None:
+                raise MetaError(
+                    f"{cls.METADATA_PATH} in '{jar.full_path}' has a "
+                    f"dependency of '{instance.modid}' without a modId"
+                )
+            if 'type' in dependency or cls.DEFAULT_TYPE is not None:
+                dep_type = str(
+                    dependency.get('type', cls.DEFAULT_TYPE)
+                ).lower()
+                if dep_type not in cls.DEPENDENCY_TYPES:
+                    raise MetaError(
+                        f"{cls.METADATA_PATH} in '{jar.full_path}' has "
+                        f"unknown dependency type '{dependency['type']}'"
+                    )
+                kind, mandatory = cls.DEPENDENCY_TYPES[dep_type]
+            else:
+                kind = 'depends'
+                mandatory = dependency.get('mandatory', False)
+
+            instance.dependencies.append(
+                Dependency(
+                    dependency['modId'],
+                    mandatory,
+                    resolve(dependency.get('versionRange', '*')),
+                    dependency.get('ordering', 'NONE'),
+                    dependency.get('side', 'BOTH'),
+                    kind
+                )
+            )
+
+        instance.environment = cls.environment_of(toml_data, instance)
+
+        # forge lists them in the manifest, neoforge in `[[mixins]]`
+        configs = manifest.get('MixinConfigs', '')
+        instance.mixin_configs = [
+            x.strip() for x in configs.split(',') if x.strip()
+        ]
+        for mixin in toml_data.get('mixins', []):
+            if isinstance(mixin, dict) and 'config' in mixin:
+                instance.mixin_configs.append(mixin['config'])
+
+        # neoforge can declare other files in `[[accessTransformers]]`
+        instance.access_files = [FORGE_TRANSFORMER]
+        for transformer in toml_data.get('accessTransformers', []):
+            if isinstance(transformer, dict) and 'file' in transformer:
+                instance.access_files.append(transformer['file'])
+
+        return instance
+
+    # forge has no field for this, but client-only mods either say so or
+    # only depend on the loader and minecraft on one side
+    @staticmethod
+    def environment_of(toml_data: Dict[str, Any], instance: ModInfo) -> str:
+        if toml_data.get('clientSideOnly', False) is True:
+            return 'client'
+
+        sides = set([
+            x.side.upper() for x in instance.dependencies
+            if x.modid in ['minecraft', 'forge', 'neoforge']
+        ])
+        if sides == {'CLIENT'}:
+            return 'client'
+        if sides == {'SERVER'}:
+            return 'server'
+        return '*'
+
+
+@define
+class NeoForgeMetadata(ForgeMetadata):
+    METADATA_PATH = "META-INF/neoforge.mods.toml"
+    LOADER = "neoforge"
+    DEFAULT_TYPE = 'required'
+
+
+# forge before 1.13, `mcmod.info` is either a list of mods or
+# `{"modListVersion": 2, "modList": [...]}`
+@define
+class LegacyForgeMetadata(ModInfo):
+    METADATA_PATH = "mcmod.info"
+    LOADER = "forge"
+
+    # `modid@[1.0,)` or just `modid`
+    @staticmethod
+    def parse_dependency(raw: str) -> Tuple[str, str]:
+        modid, _, version_range = raw.partition('@')
+        return modid.strip().lower(), version_range.strip() or '*'
+
+    @classmethod
+    def from_zip(cls, jar: DirectoryZip) -> 'LegacyForgeMetadata':
+        if not jar.has(cls.METADATA_PATH):
+            raise MetaError(f"'{jar.full_path}' has no {cls.METADATA_PATH}")
+
+        try:
+            data: Any = json.loads(
+                FileZip(cls.METADATA_PATH, jar).read().decode(
+                    errors='ignore'
+                ),
+                strict=False
+            )
+        except json.JSONDecodeError as e:
+            raise MetaError(
+                f"invalid {cls.METADATA_PATH} in '{jar.full_path}': {e}"
+            ) from e
+
+        mods = data.get('modList', []) if isinstance(data, dict) else data
+        if not isinstance(mods, list) or len(mods) == 0:
+            raise MetaError(
+                f"{cls.METADATA_PATH} in '{jar.full_path}' declares no mods"
+            )
+
+        manifest = read_manifest(jar)
+        mod = mods[0]
+
+        try:
+            modid = mod['modid']
+            instance = cls(
+                jar.full_path,
+                modid,
+                process_external_field(
+                    str(mod['version']),
+                    manifest,
+                    jar.full_path
+                ),
+                str(mod.get('name', modid)),
+                cls.LOADER
+            )
+        except (KeyError, TypeError) as e:
+            raise MetaError(
+                f"{cls.METADATA_PATH} in '{jar.full_path}' is missing {e}"
+            ) from e
+
+        # `requiredMods` have to be installed, `dependencies` only load
+        # before the mod when they are
+        required = [
+            cls.parse_dependency(x) for x in mod.get('requiredMods', [])
+            if isinstance(x, str)
+        ]
+        ordered = [
+            cls.parse_dependency(x) for x in mod.get('dependencies', [])
+            if isinstance(x, str)
+        ]
+        required_modids = [x for x, _ in required]
+        for dep_modid, version_range in required:
+            ordering = 'AFTER' if dep_modid in ordered else 'NONE'
+            instance.dependencies.append(
+                Dependency(dep_modid, True, version_range, ordering)
+            )
+        for dep_modid, version_range in ordered:
+            if dep_modid not in required_modids:
+                instance.dependencies.append(
+                    Dependency(dep_modid, False, version_range, 'AFTER')
+                )
+
+        # usually a single version, unless it's a range already
+        mcversion = str(mod.get('mcversion', '')).strip()
+        if mcversion and '$' not in mcversion:
+            if mcversion[0] not in '[(':
+                mcversion = f'[{mcversion}]'
+            instance.dependencies.append(
+                Dependency('minecraft', True, mcversion)
+            )
+
+        configs = manifest.get('MixinConfigs', '')
+        instance.mixin_configs = [
+            x.strip() for x in configs.split(',') if x.strip()
+        ]
+        if manifest.get('FMLAT', ''):
+            instance.access_files = [f"META-INF/{manifest['FMLAT']}"]
+
+        return instance
+
+
+# mod menu shows these on mods that only exist for other mods to use
+def has_library_badge(custom: Any) -> bool:
+    try:
+        return 'library' in custom['modmenu']['badges']
+    except (KeyError, TypeError):
+        return False
+
+
+# modids and names like `geckolib`, `owo-lib` or `Cloth Config API`
+LIBRARY_NAME = re.compile(r'(?:lib|library|api)(?![a-z])', re.IGNORECASE)
+
+
+def is_library_name(modid: str, name: str) -> bool:
+    return any([LIBRARY_NAME.search(x) for x in [modid, name]])
+
+
+@define
+class FabricMetadata(ModInfo):
+    METADATA_PATH = "fabric.mod.json"
+    LOADER = "fabric"
+
+    DEPENDENCY_KINDS = {
+        'depends':      True,
+        'recommends':   False,
+        'suggests':     False,
+        'conflicts':    False,
+        'breaks':       False,
+    }
+
+    @classmethod
+    def from_zip(cls, jar: DirectoryZip) -> 'FabricMetadata':
+        if not jar.has(cls.METADATA_PATH):
+            raise MetaError(f"'{jar.full_path}' has no {cls.METADATA_PATH}")
+
+        try:
+            # fabric's own parser tolerates raw control characters in strings
+            data: Dict[str, Any] = json.loads(
+                FileZip(cls.METADATA_PATH, jar).read().decode(),
+                strict=False
+            )
+            modid = data['id']
+            version = data['version']
+        except (json.JSONDecodeError, UnicodeDecodeError) as e:
+            raise MetaError(
+                f"invalid {cls.METADATA_PATH} in '{jar.full_path}': {e}"
+            ) from e
+        except KeyError as e:
+            raise MetaError(
+                f"{cls.METADATA_PATH} in '{jar.full_path}' is missing {e}"
+            ) from e
+
+        instance = cls(
+            jar.full_path,
+            modid,
+            version,
+            data.get('name', modid),
+            cls.LOADER,
+            environment=data.get('environment', '*')
+        )
+
+        for mixin in data.get('mixins', []):
+            if isinstance(mixin, dict):
+                mixin = mixin.get('config', None)
+            if isinstance(mixin, str):
+                instance.mixin_configs.append(mixin)
+
+        instance.provides = [
+            x for x in data.get('provides', []) if isinstance(x, str)
+        ]
+        instance.library = has_library_badge(data.get('custom', {}))
+        if isinstance(data.get('accessWidener', None), str):
+            instance.access_files.append(data['accessWidener'])
+
+        for kind, mandatory in cls.DEPENDENCY_KINDS.items():
+            for dep_modid, ranges in data.get(kind, {}).items():
+                # a list of ranges means any one of them has to match
+                if isinstance(ranges, list):
+                    version_range = ' || '.join(ranges)
+                else:
+                    version_range = ranges
+                instance.dependencies.append(
+                    Dependency(
+                        dep_modid,
+                        mandatory,
+                        version_range,
+                        kind=kind
+                    )
+                )
+
+        return instance
+
+
+@define
+class QuiltMetadata(ModInfo):
+    METADATA_PATH = "quilt.mod.json"
+    LOADER = "quilt"
+
+    ENVIRONMENTS = {
+        'client': 'client',
+        'dedicated_server': 'server',
+    }
+
+    # `maven.group:modid` and `modid` both refer to `modid`
+    @staticmethod
+    def modid_of(raw: str) -> str:
+        return raw.split(':')[-1]
+
+    # a string, a list of which any has to match, or `{"any": [...]}` and
+    # `{"all": [...]}` objects
+    @classmethod
+    def version_range_of(cls, versions: Any) -> str:
+        if isinstance(versions, str):
+            return versions
+        if isinstance(versions, list):
+            return ' || '.join([cls.version_range_of(x) for x in versions])
+        if isinstance(versions, dict):
+            if 'any' in versions:
+                return cls.version_range_of(versions['any'])
+            if 'all' in versions:
+                return ' '.join(
+                    [cls.version_range_of(x) for x in versions['all']]
+                )
+        return '*'
+
+    @classmethod
+    def dependency_of(cls, raw: Any, kind: str) -> Optional[Dependency]:
+        # a list of alternatives, only the first one is checked
+        if isinstance(raw, list):
+            raw = raw[0] if len(raw) > 0 else None
+        if isinstance(raw, str):
+            raw = {'id': raw}
+        if not isinstance(raw, dict) or 'id' not in raw:
+            return None
+
+        # optional dependencies work like fabric's `recommends`
+        if kind == 'depends' and raw.get('optional', False):
+            kind = 'recommends'
+        side = cls.ENVIRONMENTS.get(raw.get('environment', '*'), 'both')
+        return Dependency(
+            cls.modid_of(raw['id']),
+            kind == 'depends',
+            cls.version_range_of(raw.get('versions', '*')),
+            side=side.upper(),
+            kind=kind
+        )
+
+    @classmethod
+    def from_zip(cls, jar: DirectoryZip) -> 'QuiltMetadata':
+        if not jar.has(cls.METADATA_PATH):
+            raise MetaError(f"'{jar.full_path}' has no {cls.METADATA_PATH}")
+
+        try:
+            data: Dict[str, Any] = json.loads(
+                FileZip(cls.METADATA_PATH, jar).read().decode(),
+                strict=False
+            )
+            loader_data: Dict[str, Any] = data['quilt_loader']
+            modid = loader_data['id']
+            version = loader_data['version']
+        except (json.JSONDecodeError, UnicodeDecodeError) as e:
+            raise MetaError(
+                f"invalid {cls.METADATA_PATH} in '{jar.full_path}': {e}"
+            ) from e
+        except (KeyError, TypeError) as e:
+            raise MetaError(
+                f"{cls.METADATA_PATH} in '{jar.full_path}' is missing {e}"
+            ) from e
+
+        metadata = loader_data.get('metadata', {})
+        minecraft = data.get('minecraft', {})
+        instance = cls(
+            jar.full_path,
+            modid,
+            version,
+            metadata.get('name', modid),
+            cls.LOADER,
+            environment=cls.ENVIRONMENTS.get(
+                minecraft.get('environment', '*'),
+                '*'
+            )
+        )
+
+        mixins = data.get('mixin', [])
+        for mixin in [mixins] if isinstance(mixins, str) else mixins:
+            if isinstance(mixin, str):
+                instance.mixin_configs.append(mixin)
+
+        instance.library = has_library_badge(data)
+        wideners = data.get('access_widener', [])
+        for widener in [wideners] if isinstance(wideners, str) else wideners:
+            if isinstance(widener, str):
+                instance.access_files.append(widener)
+
+        for provided in loader_data.get('provides', []):
+            if isinstance(provided, dict):
+                provided = provided.get('id', None)
+            if isinstance(provided, str):
+                instance.provides.append(cls.modid_of(provided))
+
+        for kind in ['depends', 'breaks']:
+            for raw in loader_data.get(kind, []):
+                dependency = cls.dependency_of(raw, kind)
+                if dependency is not None:
+                    instance.dependencies.append(dependency)
+
+        return instance
+
+
+METADATA_FORMATS: List[Type[ModInfo]] = [
+    NeoForgeMetadata,
+    ForgeMetadata,
+    QuiltMetadata,
+    FabricMetadata,
+    LegacyForgeMetadata,
+]
+
+# modids that identify the loader of a profile
+LOADER_MODIDS: Dict[str, str] = {
+    'neoforge': 'neoforge',
+    'forge': 'forge',
+    'quilt_loader': 'quilt',
+    'fabricloader': 'fabric',
+}
+
+
+# jars can ship metadata for several loaders, so prefer the format of the
+# profile's loader, and forge over neoforge when the loader is unknown.
+# quilt loads fabric mods too, so those are the fallback in quilt profiles.
+# `mcmod.info` is only used without a `mods.toml`
+def read_metadata(
+            jar: DirectoryZip,
+            loader: Optional[str] = None
+        ) -> Optional[ModInfo]:
+    formats = [x for x in METADATA_FORMATS if jar.has(x.METADATA_PATH)]
+    formats.sort(key=lambda x: x is LegacyForgeMetadata)
+    if loader != 'neoforge':
+        formats.sort(key=lambda x: x is NeoForgeMetadata)
+    if loader != 'quilt':
+        formats.sort(key=lambda x: x is QuiltMetadata)
+    formats.sort(key=lambda x: x.LOADER != loader)
+
+    error: Optional[MetaError] = None
+    for format in formats:
+        log.log(TRACE, f"'{jar.full_path}': trying {format.METADATA_PATH}")
+        try:
+            return format.from_zip(jar)
+        except MetaError as e:
+            error = error or e
+
+    if error is not None:
+        raise error
+    return None
+
+
+# folders in `assets/<namespace>/` and `data/<namespace>/` that add
+# something to the game, unlike translations or icons
+CONTENT_FOLDERS: Dict[str, List[str]] = {
+    'assets': [
+        'blockstates', 'models', 'textures', 'sounds', 'particles', 'shaders',
+    ],
+    'data': [
+        'recipes', 'recipe', 'loot_tables', 'loot_table', 'advancements',
+        'advancement', 'worldgen', 'structures', 'structure', 'dimension',
+        'dimension_type',
+    ],
+}
+
+
+def has_content(jar: DirectoryZip) -> bool:
+    for item in jar.list():
+        parts = item.name.split('/')
+        if len(parts) > 3 and parts[2] in CONTENT_FOLDERS.get(parts[0], []):
+            return True
+    return False
+
+
+# jar-in-jar mods are usually bundled in `META-INF/jarjar/` (forge) or
+# `META-INF/jars/` (fabric), but any nested jar is scanned
+MAX_NESTING_DEPTH = 4
+
+
+@define
+class ScanResult:
+    file:   str
+    mods:   List[ModInfo] = Factory(list)
+    errors: List[str] = Factory(list)
+    # whether the jar or any nested jar contained metadata at all
+    found:  bool = False
+
+
+def scan_jar(
+            jar: DirectoryZip,
+            loader: Optional[str] = None,
+            result: Optional[ScanResult] = None,
+            nested_in: Optional[str] = None,
+            seen: Optional[Set[str]] = None,
+        ) -> ScanResult:
+    if result is None:
+        result = ScanResult(jar.full_path)
+    # hashes of the jars enclosing this one, to catch self-referencing jars
+    seen = set() if seen is None else seen
+
+    info: Optional[ModInfo] = None
+    try:
+        info = read_metadata(jar, loader)
+    except MetaError as e:
+        log.debug(f"'{jar.full_path}': {e}")
+        result.found = True
+        result.errors.append(str(e))
+
+    if info is not None:
+        log.debug(
+            f"'{jar.full_path}': {info.loader} mod '{info.modid}' "
+            f"{info.version}"
+        )
+        result.found = True
+        info.jar_file = result.file
+        info.nested_in = nested_in
+        info.java_version = jar_java_version(jar)
+        info.mixin_configs = find_configs(jar, info.mixin_configs)
+        info.mixins = read_mixins(jar, info.mixin_configs)
+        info.missing_refmaps = find_missing_refmaps(jar, info.mixin_configs)
+        info.access = read_access(jar, info.access_files)
+        # mixins change the game too, eg: performance mods
+        info.content = len(info.mixins) > 0 or has_content(jar)
+        result.mods.append(info)
+
+    for item in [x for x in jar.list() if x.name.endswith('.jar')]:
+        if len(seen) >= MAX_NESTING_DEPTH:
+            result.errors.append(
+                f"'{jar.full_path}' nests jars deeper than "
+                f"{MAX_NESTING_DEPTH} levels, skipping '{item.name}'"
+            )
+            continue
+
+        log.log(TRACE, f"'{jar.full_path}': scanning nested '{item.name}'")
+        data = cast(ZipFile, jar._zip).read(item.name)
+        digest = hashlib.sha1(data).hexdigest()
+        if digest in seen:
+            result.errors.append(
+                f"'{jar.full_path}' contains itself as '{item.name}'"
+            )
+            continue
+
+        try:
+            with io.BytesIO(data) as nested_jar_bytes:
+                with ZipFile(nested_jar_bytes, 'r') as nested_jar:
+                    scan_jar(
+                        DirectoryZip(jar, item.name, nested_jar),
+                        loader,
+                        result,
+                        info.file if info is not None else nested_in,
+                        seen | {digest}
+                    )  # yay recursion
+        except BadZipFile as e:
+            result.errors.append(
+                f"invalid nested jar '{item.name}' in '{jar.full_path}': {e}"
+            )
+
+    return result
+
+
+# scans a jar in the mods folder, used by the worker processes
+def scan_file(
+            directory: str,
+            name: str,
+            loader: Optional[str] = None
+        ) -> ScanResult:
+    mod_dir = DirectoryReal(None, directory)
+    try:
+        with ZipFile(os.path.join(directory, name), 'r') as jar:
+            return scan_jar(DirectoryZip(mod_dir, name, jar), loader)
+    except (OSError, BadZipFile) as e:
+        result = ScanResult(os.path.join(directory, name), found=True)
+        result.errors.append(f"failed to open jar '{name}': {e}")
+        return result
+
+
+# jars built in memory, by file name
+def zip_of(name: str, files: Dict[str, str]) -> DirectoryZip:
+    data = io.BytesIO()
+    with ZipFile(data, 'w') as jar:
+        for path, content in files.items():
+            jar.writestr(path, content)
+    return DirectoryZip(None, name, ZipFile(data, 'r'))
+
+
+def test():
+    simple = read_metadata(zip_of('simple.jar', {
+        'META-INF/mods.toml': '\n'.join([
+            'modLoader="javafml"',
+            'loaderVersion="[47,)"',
+            'license="MIT"',
+            '[[mods]]',
+            'modId="simple"',
+            'version="1.0.0"',
+        ]),
+    }), 'forge')
+    print('===========================================================')
+    print(simple)
+    print(
+        '[PASS]' if simple is not None and simple.modid == 'simple'
+        and simple.version == '1.0.0' and simple.loader == 'forge'
+        and simple.dependencies == [] else '[FAIL]'
+    )
+
+    several = read_metadata(zip_of('several.jar', {
+        'META-INF/mods.toml': '\n'.join([
+            'modLoader="javafml"',
+            'loaderVersion="[47,)"',
+            'license="MIT"',
+            '[[mods]]',
+            'modId="several"',
+            'version="2.1.0"',
+            '[[dependencies.several]]',
+            'modId="forge"',
+            'mandatory=true',
+            'versionRange="[47,)"',
+            'ordering="NONE"',
+            'side="BOTH"',
+            '[[dependencies.several]]',
+            'modId="jei"',
+            'mandatory=false',
+            'versionRange="[15,)"',
+            'ordering="AFTER"',
+            'side="CLIENT"',
+            '[[dependencies.several]]',
+            'modId="optifine"',
+            'type="incompatible"',
+        ]),
+    }), 'forge')
+    print('===========================================================')
+    print(several)
+    print(
+        '[PASS]' if several is not None and several.modid == 'several'
+        and several.version == '2.1.0' and several.dependencies == [
+            Dependency('forge', True, '[47,)'),
+            Dependency('jei', False, '[15,)', 'AFTER', 'CLIENT'),
+            Dependency('optifine', False, '*', kind='breaks'),
+        ] else '[FAIL]'
+    )
+
+    fabric = read_metadata(zip_of('fabric.jar', {
+        'fabric.mod.json': json.dumps({
+            'schemaVersion': 1,
+            'id': 'fabric_several',
+            'version': '3.0.0',
+            'depends': {'fabricloader': '>=0.15', 'java': '>=17'},
+            'recommends': {'modmenu': '*'},
+            'breaks': {'sodium': ['<0.5', '>=0.6']},
+        }),
+    }), 'fabric')
+    print('===========================================================')
+    print(fabric)
+    print(
+        '[PASS]' if fabric is not None and fabric.modid == 'fabric_several'
+        and fabric.version == '3.0.0' and [
+            (x.modid, x.mandatory, x.version_range, x.kind)
+            for x in fabric.dependencies
+        ] == [
+            ('fabricloader', True, '>=0.15', 'depends'),
+            ('java', True, '>=17', 'depends'),
+            ('modmenu', False, '*', 'recommends'),
+            ('sodium', False, '<0.5 || >=0.6', 'breaks'),
+        ] else '[FAIL]'
+    )
+
+    # `${file.jarVersion}` comes from the manifest, and stays unknown
+    # without one
+    from mod_info import Mod, ModPack
+
+    jar_version = '\n'.join([
+        'modLoader="javafml"',
+        'loaderVersion="[47,)"',
+        'license="MIT"',
+        '[[mods]]',
+        'modId="jarversion"',
+        'version="${file.jarVersion}"',
+    ])
+    resolved = read_metadata(zip_of('resolved.jar', {
+        'META-INF/mods.toml': jar_version,
+        'META-INF/MANIFEST.MF': '\r\n'.join([
+            'Manifest-Version: 1.0',
+            'Implementation-Version: 4.5.6',
+            '',
+        ]),
+    }), 'forge')
+    print('===========================================================')
+    print(resolved)
+    print(
+        '[PASS]' if resolved is not None and resolved.version == '4.5.6'
+        else '[FAIL]'
+    )
+
+    unresolved = read_metadata(zip_of('unresolved.jar', {
+        'META-INF/mods.toml': jar_version,
+    }), 'forge')
+    print('===========================================================')
+    mod = None
+    if unresolved is not None:
+        mod = Mod.load(cast(ModPack, None), unresolved)
+        print(mod.errors)
+    print(
+        '[PASS]' if mod is not None and mod._version.text == '*'
+        and any(['unknown version' in x for x in mod.errors]) else '[FAIL]'
+    )
+
+    # neoforge dependencies without a `type` are required
+    neoforge = read_metadata(zip_of('neoforge.jar', {
+        'META-INF/neoforge.mods.toml': '\n'.join([
+            'modLoader="javafml"',
+            'loaderVersion="[1,)"',
+            'license="MIT"',
+            '[[mods]]',
+            'modId="neo"',
+            'version="1.0.0"',
+            '[[dependencies.neo]]',
+            'modId="neoforge"',
+            'versionRange="[20.4,)"',
+            '[[dependencies.neo]]',
+            'modId="jei"',
+            'type="optional"',
+        ]),
+    }), 'neoforge')
+    print('===========================================================')
+    print(neoforge)
+    print(
+        '[PASS]' if neoforge is not None and neoforge.loader == 'neoforge'
+        and [(x.modid, x.mandatory) for x in neoforge.dependencies] == [
+            ('neoforge', True),
+            ('jei', False),
+        ] else '[FAIL]'
+    )
+
+    print('===========================================================')
+    try:
+        read_metadata(zip_of('broken.jar', {
+            'META-INF/mods.toml': '\n'.join([
+                '[[mods]]',
+                'modId="broken"',
+                'version="1.0.0"',
+                '[[dependencies.broken]]',
+                'mandatory=true',
+            ]),
+        }), 'forge')
+        print('[FAIL]: no error for a dependency without a modId')
+    except MetaError as e:
+        print(e)
+        print('[PASS]')
+
+
+if __name__ == '__main__':
+    import sys
+    if len(sys.argv) == 1:
+        test()
+    for path in sys.argv[1:]:
+        for format in METADATA_FORMATS:
+            try:
+                print(format.from_jar(path))
+            except MetaError as e:
+                print(e)
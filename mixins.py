@@ -0,0 +1,153 @@
+
+from attrs import define, Factory
+
+from typing import Any, Dict, List
+from zipfile import ZipFile
+import json
+
+from classfile import parse_class, find_annotation, ClassFormatError
+from filesystem import DirectoryZip, FileZip
+from output import MixinOverlapReport
+
+
+MIXIN = 'Lorg/spongepowered/asm/mixin/Mixin;'
+OVERWRITE = 'Lorg/spongepowered/asm/mixin/Overwrite;'
+# these only expose members of the target, they don't change its behaviour
+PASSIVE = [
+    'Lorg/spongepowered/asm/mixin/gen/Accessor;',
+    'Lorg/spongepowered/asm/mixin/gen/Invoker;',
+]
+
+
+@define
+class Mixin:
+    # eg: `com.example.mixin.MinecraftMixin`
+    name:       str
+    config:     str
+    # eg: `net.minecraft.client.Minecraft`
+    targets:    List[str] = Factory(list)
+    # methods of the targets replaced with `@Overwrite`
+    overwrites: List[str] = Factory(list)
+    # only accessors and invokers
+    passive:    bool = False
+
+
+# `Lnet/minecraft/Foo;` and `net/minecraft/Foo` become `net.minecraft.Foo`
+def class_name(target: str) -> str:
+    if target.startswith('L') and target.endswith(';'):
+        target = target[1:-1]
+    return target.replace('/', '.')
+
+
+def read_config(jar: DirectoryZip, path: str) -> List[str]:
+    data: Dict[str, Any] = json.loads(
+        FileZip(path, jar).read().decode(),
+        strict=False
+    )
+    package = data.get('package', '')
+    names: List[str] = []
+    for side in ['mixins', 'client', 'server']:
+        names.extend([x for x in data.get(side, None) or []])
+    return [f'{package}.{x}' if package else x for x in names]
+
+
+# refmaps the configs name that the jar doesn't contain, by config, usually
+# a jar built in a dev environment
+def find_missing_refmaps(
+            jar: DirectoryZip,
+            configs: List[str]
+        ) -> Dict[str, str]:
+    missing: Dict[str, str] = {}
+    for config in configs:
+        try:
+            data: Dict[str, Any] = json.loads(
+                FileZip(config, jar).read().decode(),
+                strict=False
+            )
+            refmap = data.get('refmap', None)
+        except (ValueError, UnicodeDecodeError, AttributeError, TypeError):
+            continue
+        if isinstance(refmap, str) and refmap and not jar.has(refmap):
+            missing[config] = refmap
+    return missing
+
+
+def read_mixin(zip_file: ZipFile, name: str, config: str) -> Mixin:
+    mixin = Mixin(name, config)
+
+    path = name.replace('.', '/') + '.class'
+    try:
+        parsed = parse_class(zip_file.read(path))
+    except (KeyError, ClassFormatError):
+        return mixin
+
+    annotation = find_annotation(parsed.annotations, MIXIN)
+    if annotation is not None:
+        for key in ['value', 'targets']:
+            values = annotation.values.get(key, [])
+            if not isinstance(values, list):
+                values = [values]
+            mixin.targets.extend([class_name(x) for x in values if x])
+
+    passive = True
+    for method in parsed.methods:
+        if method.name in ['<init>', '<clinit>']:
+            continue
+        types = [x.type for x in method.annotations]
+        if OVERWRITE in types:
+            mixin.overwrites.append(method.name)
+        if not any([x in PASSIVE for x in types]):
+            passive = False
+    mixin.passive = passive
+
+    return mixin
+
+
+# the mixin configs of the jar, either the declared ones or the
+# `*.mixins.json` files at its root
+def find_configs(jar: DirectoryZip, declared: List[str]) -> List[str]:
+    configs = [x for x in declared if jar.has(x)]
+    if len(declared) > 0:
+        return configs
+    return sorted([
+        x.name for x in jar.list()
+        if '/' not in x.name and x.name.endswith('.mixins.json')
+    ])
+
+
+def read_mixins(jar: DirectoryZip, configs: List[str]) -> List[Mixin]:
+    mixins: List[Mixin] = []
+    if not isinstance(jar._zip, ZipFile):
+        return mixins
+
+    for config in configs:
+        try:
+            names = read_config(jar, config)
+        except (ValueError, UnicodeDecodeError, AttributeError, TypeError):
+            continue
+        mixins.extend([read_mixin(jar._zip, x, config) for x in names])
+
+    return mixins
+
+
+# classes changed by mixins of more than one mod
+def find_overlaps(mixins: Dict[str, List[Mixin]]) -> List[MixinOverlapReport]:
+    targets: Dict[str, MixinOverlapReport] = {}
+    for modid in sorted(mixins.keys()):
+        for mixin in mixins[modid]:
+            if mixin.passive:
+                continue
+            for target in mixin.targets:
+                report = targets.setdefault(target, MixinOverlapReport(target))
+                if modid not in report.mods:
+                    report.mods.append(modid)
+                if len(mixin.overwrites) > 0:
+                    report.overwrites.setdefault(modid, [])
+                    for method in mixin.overwrites:
+                        if method not in report.overwrites[modid]:
+                            report.overwrites[modid].append(method)
+
+    overlaps = [x for x in targets.values() if len(x.mods) > 1]
+    # overwrites are the ones most likely to break each other
+    overlaps.sort(key=lambda x: (len(x.overwrites) == 0, x.target))
+    return overlaps
@@ -2,16 +2,40 @@
 from pygtail import Pygtail  # type: ignore
 from attrs import define
 from tqdm import tqdm
-import toml
 
-from typing import cast, List, Dict, Union, Any, Optional, Set
-from zipfile import ZipFile
-import io
+from typing import TYPE_CHECKING, cast, List, Dict, Optional, Set
+from concurrent.futures import ProcessPoolExecutor
+from functools import partial
+import logging
+import fnmatch
 import os
-import re
 
-from filesystem import FileBase, FileReal, DirectoryZip, DirectoryReal, FileZip
+from filesystem import FileBase, FileReal, DirectoryReal
+from metadata import (
+    ModInfo, ScanResult, scan_file, has_placeholder, is_library_name
+)
+from cache import ScanCache
+from mixins import find_overlaps
+from access import find_access_overlaps
+from incompat import Incompatibility
+from fuzzy import closest_modids
+import graph
+from output import (
+    DependencyReport, ModReport, ModInfoReport, ModErrorsReport,
+    ValidateReport, WhyDependsReport, ChainReport, UnsatisfiedEdgeReport,
+    DuplicateReport, DuplicateFileReport, CycleReport,
+    LoadOrderReport, ConflictReport, OrphanReport, DependencyTreeReport,
+    RecommendationReport, IncompatibilityReport, InstallOrderReport,
+    InstallStepReport, CandidateReport
+)
 from version import VersionRange, Version, BadVersionString
+from logger import TRACE
+
+if TYPE_CHECKING:
+    from remote import ModrinthResolver
+
+
+log = logging.getLogger(__name__)
 
 
 class DependencyFailure(Exception):
@@ -22,36 +46,44 @@ class ModDependency:
     modid: str
     required: bool
     version_reqs: List[VersionRange]
-
-    def __init__(self, modid: str, required: bool, version_range: str):
+    ordering: str
+    side: str
+    # see `metadata.Dependency.kind`
+    kind: str
+
+    def __init__(self,
+                modid: str,
+                required: bool,
+                version_range: str,
+                ordering: str = 'NONE',
+                side: str = 'BOTH',
+                loader: str = 'forge',
+                kind: str = 'depends'
+            ):
         self.modid = modid
         self.required = required
-        self.version_reqs = VersionRange.fromString(version_range)
+        self.version_reqs = VersionRange.fromLoaderString(
+            version_range,
+            loader
+        )
+        self.ordering = ordering
+        self.side = side
+        self.kind = kind
 
     def __str__(self) -> str:
         return ','.join([str(req) for req in self.version_reqs])
 
     def validateMod(self, mod: 'Mod') -> bool:
-        if mod.modid != self.modid:
+        if mod.modid != self.modid and self.modid not in mod.provides:
             return False
+        if mod.any_version:
+            return True
         for version_req in self.version_reqs:
             if version_req.contains(mod._version):
                 return True
         return False
 
 
-MANIFEST_MAPPING: Dict[str, Union[str, List[str]]] = {
-    'file.jarVersion': [
-        'Implementation-Version',
-        'Specification-Version',
-        'Manifest-Version'
-    ],
-    # temp solution until I learn where to actually get these from
-    'forge_version_range': '*',
-    'minecraft_version_range': '*',
-}
-
-
 class Mod:
     filename:       str
     name:           str
@@ -59,19 +91,73 @@ class Mod:
     _version:       Version
     dependencies:   List[ModDependency]
     dependents:     List[ModDependency]
+    conflicts:      List[ModDependency]
+    # `java` isn't a mod, these are only checked against `--java`
+    java_reqs:      List[ModDependency]
     errors:         List[str]
+    # set by `--lie-depends`, treats all dependencies as satisfied
+    lie_depends:    bool
+    # the jar (or the jar it is nested in) ends with `.disabled`
+    disabled:       bool
+    # overridden with `<modid>=*`, whether or not a jar provides it
+    any_override:   bool
 
     pack:           'ModPack'
-    manifest:       Dict[str, str]
-    toml_data:      Dict[str, Any]
+    # not set for stand-ins provided by `--override-versions`
+    info:           Optional[ModInfo]
     parent:         Optional['Mod']
 
     def __init__(self, pack: 'ModPack'):
         self.dependencies = []
         self.dependents = []
+        self.conflicts = []
+        self.java_reqs = []
         self.errors = []
-        self.manifest = {}
+        self.lie_depends = False
+        self.disabled = False
+        self.any_override = False
         self.pack = pack
+        self.info = None
+        self.parent = None
+
+    @property
+    def loader(self) -> Optional[str]:
+        return self.info.loader if self.info is not None else None
+
+    # `client`, `server` or `*`
+    @property
+    def environment(self) -> str:
+        return self.info.environment if self.info is not None else '*'
+
+    @property
+    def java_version(self) -> Optional[int]:
+        return self.info.java_version if self.info is not None else None
+
+    # mods overridden with `<modid>=*` satisfy any version range
+    @property
+    def any_version(self) -> bool:
+        return self.any_override
+
+    # other modids this mod satisfies dependencies on
+    @property
+    def provides(self) -> List[str]:
+        return self.info.provides if self.info is not None else []
+
+    # disabled mods don't satisfy dependencies unless `--include-disabled`
+    @property
+    def active(self) -> bool:
+        return not self.disabled or self.pack.include_disabled
+
+    @property
+    def nested(self) -> bool:
+        return self.jar_file != self.filename
+
+    # the file in the mods folder that provides this mod
+    @property
+    def jar_file(self) -> str:
+        if self.info is not None and self.info.jar_file:
+            return self.info.jar_file
+        return self.filename
 
     def enable(self) -> None:
         if self.filename.endswith('.jar.disabled'):
@@ -94,221 +180,556 @@ class Mod:
                     self.pack.mods[dep.modid].enable()
 
     @classmethod
-    def load(cls,
-                pack: 'ModPack',
-                filename: str,
-                toml_data: Dict[str, Any],
-                manifest: str
-            ) -> 'Mod':
+    def load(cls, pack: 'ModPack', info: ModInfo) -> 'Mod':
         instance = cls(pack)
-        instance.filename = filename
-
-        if manifest != "":
-            manifest = manifest.replace('\r\n', '\n')
-            while '\n\n' in manifest:
-                manifest = manifest.replace('\n\n', '\n')
-
-            for line in manifest.split('\n'):
-                parts = line.split(':')
-                if len(parts) == 2:
-                    instance.manifest[parts[0].strip()] = parts[1].strip()
-
-        def processExternalField(field_raw: str) -> str:
-            # checks if string is an external reference `${<var_name>}`
-            extern = re.match(r'\${([^}]+)}', field_raw)
-            if not extern:
-                return field_raw
-
-            field = cast(str, extern.groups(1)[0])
-            map = MANIFEST_MAPPING.get(field, None)
-
-            if type(map) is str:
-                return map
-            elif type(map) is list:
-                result: str = ""
-                for key in map:
-
-                    result = instance.manifest.get(key, "")
-                    if result:
-                        break
-
-                if result == "":
-                    raise ValueError(
-                        f"failed to process field value {field_raw}"
-                    )
-                return result
+        instance.info = info
+        instance.filename = info.file
+        instance.modid = info.modid
+        instance.name = info.name
+        # eg: a `${file.jarVersion}` without an `Implementation-Version`
+        if has_placeholder(info.version):
+            instance._version = Version.fromString('*')
+            instance.errors.append(
+                f"'{instance.name}' has an unknown version, "
+                f"'{info.version}' could not be resolved"
+            )
+        else:
+            try:
+                instance._version = Version.fromString(info.version)
+            # eg: `"version": "dev"` in a `fabric.mod.json`
+            except BadVersionString:
+                instance._version = Version.fromString('*')
+                instance.errors.append(
+                    f"'{instance.name}' has an unknown version, "
+                    f"'{info.version}' is not a valid version"
+                )
 
-            elif map is None:
-                return field_raw
+        for dependency in info.dependencies:
+            if dependency.kind in ['conflicts', 'breaks']:
+                target = instance.conflicts
+            elif dependency.modid == 'java':
+                target = instance.java_reqs
             else:
-                raise ValueError(f"failed to process field value {field_raw}")
-
-        if "mods" in toml_data and len(toml_data['mods']) > 0:
-            mod = toml_data['mods'][0]
+                target = instance.dependencies
+            try:
+                target.append(
+                    ModDependency(
+                        dependency.modid,
+                        dependency.mandatory,
+                        dependency.version_range,
+                        dependency.ordering,
+                        dependency.side,
+                        info.loader,
+                        dependency.kind
+                    )
+                )
+            except BadVersionString as e:
+                instance.errors.append(
+                    f"'{instance.name}' dependency "
+                    f"'{dependency.modid}' has invalid "
+                    f"version range '{dependency.version_range}'"
+                )
 
-            instance.modid = processExternalField(mod['modId'])
-            instance._version = Version.fromString(
-                processExternalField(mod['version'])
+        for config, refmap in info.missing_refmaps.items():
+            instance.errors.append(
+                f"'{instance.name}' mixin config '{config}' uses refmap "
+                f"'{refmap}', which is missing from the jar"
             )
-            instance.name = processExternalField(mod["displayName"])
-            instance.toml_data = toml_data
-
-            toml_deps_len = len(toml_data["dependencies"])
-            if "dependencies" in toml_data and toml_deps_len > 0:
-                deps = toml_data["dependencies"]
-                if instance.modid in deps and len(deps[instance.modid]) > 0:
-                    for dependency in deps[instance.modid]:
-                        try:
-                            version_range = processExternalField(
-                                dependency['versionRange']
-                            )
-                            instance.dependencies.append(
-                                ModDependency(
-                                    dependency["modId"],
-                                    dependency['mandatory'],
-                                    version_range
-                                )
-                            )
-                        except BadVersionString as e:
-                            instance.errors.append(
-                                f"'{instance.name}' dependency "
-                                f"'{dependency['modId']}' has invalid "
-                                f"version range '{dependency['versionRange']}'"
-                            )
 
         return instance
 
 
-class DependencyGraph:
-    _ALL_GRAPHS:    Dict[str, 'DependencyGraph'] = {}
-    _ALL_NODES:     Dict[str, 'Node'] = {}
-
-    class Node:
-        mod_set:    Set[Mod]
-        graph:      'DependencyGraph'
-
-        def __init__(self, mod: Mod, graph: 'DependencyGraph'):
-            self.mod_set = {mod}
-            self.graph = graph
-            if mod.modid in DependencyGraph._ALL_NODES:
-                raise ValueError(f"modid '{mod.modid}' already has a node")
-
-        def merge(self, other: 'DependencyGraph.Node') -> None:
-            self.mod_set.union(other.mod_set)
-            for mod in other.mod_set:
-                DependencyGraph._ALL_GRAPHS[mod.modid] = self.graph
-                DependencyGraph._ALL_NODES[mod.modid] = self
-            other.mod_set = set()
-
-        @property
-        def dependencies(self) -> List[ModDependency]:
-            deps = []
-            for mod in self.mod_set:
-                deps.extend(mod.dependencies)
-            return deps
-
-        @property
-        def dependents(self) -> List[ModDependency]:
-            deps = []
-            for mod in self.mod_set:
-                deps.extend(mod.dependents)
-            return deps
-
-    nodes: List[Node]
-
-    def __init__(self, mod: Mod):
-        self.nodes = [DependencyGraph.Node(mod, self)]
-
-    def merge(self, other: 'DependencyGraph') -> None:
-        for node in other.nodes:
-            for mod in node.mod_set:
-                DependencyGraph._ALL_GRAPHS[mod.modid] = self
-                DependencyGraph._ALL_NODES[mod.modid] = node
-            node.graph = self
-            self.nodes.append(node)
-        other.nodes = []
-
-    def disable_all(self) -> None:
-        for node in self.nodes:
-            for mod in node.mod_set:
-                mod.disable()
-
-    def enable_all(self) -> None:
-        for node in self.nodes:
-            for mod in node.mod_set:
-                mod.enable()
-
-
 class ModPack:
     directory:  DirectoryReal
+    loader:     Optional[str]
+    # the active mod for each modid
     mods:       Dict[str, Mod]
+    # every mod found, including disabled, nested and duplicate ones
+    all_mods:   List[Mod]
+    # modids provided by an active mod, to the modid of that mod
+    aliases:    Dict[str, str]
     errors:     List[str]
-
-    def __init__(self, directory: DirectoryReal):
+    # treats `.jar.disabled` files as if they were enabled
+    include_disabled: bool
+
+    def __init__(
+                self,
+                directory: DirectoryReal,
+                loader: Optional[str] = None,
+                include_disabled: bool = False
+            ):
         self.directory = directory
+        self.loader = loader
         self.mods = {}
+        self.all_mods = []
+        self.aliases = {}
         self.errors = []
+        self.include_disabled = include_disabled
 
-    def process_jar(self, jar: DirectoryZip) -> bool:
-        found = False
-
-        for item in [x for x in jar.list() if x.name.endswith('.jar')]:
-            with io.BytesIO(
-                        cast(ZipFile, jar._zip).read(item.name)
-                    ) as nested_jar_bytes:
-                with ZipFile(nested_jar_bytes, 'r') as nested_jar:
-                    _dir = DirectoryZip(jar, item.name, nested_jar)
-                    found = found or self.process_jar(_dir)  # yay recursion
-
-        if jar.has("META-INF/mods.toml"):
-            found = True
-            toml_data = toml.loads(
-                FileZip("META-INF/mods.toml", jar).read().decode()
-            )
-            manifest = ""
-            if jar.has("META-INF/MANIFEST.MF"):
-                manifest = FileZip(
-                    "META-INF/MANIFEST.MF",
-                    jar
-                ).read().decode()
-
-            mod = Mod.load(self, jar.full_path, toml_data, manifest)
-            if hasattr(mod, 'modid'):
-                self.mods[mod.modid] = mod
-        return found
-
-    def load(self) -> bool:
+    def register(self, mod: Mod) -> None:
+        self.all_mods.append(mod)
+        if not mod.active:
+            return
+
+        # top-level jars take precedence over nested ones
+        current = self.mods.get(mod.modid, None)
+        if current is None or current.nested:
+            self.mods[mod.modid] = mod
+        elif not mod.nested:
+            self.mods[mod.modid] = mod
+
+        for modid in mod.provides:
+            self.aliases.setdefault(modid, mod.modid)
+
+    # the mod satisfying dependencies on `modid`, either a mod with that
+    # modid or one providing it
+    def provider(self, modid: str) -> Optional[Mod]:
+        if modid in self.mods:
+            return self.mods[modid]
+        if modid in self.aliases:
+            return self.mods.get(self.aliases[modid], None)
+        return None
+
+    def process_jar(self, result: ScanResult, disabled: bool) -> bool:
+        self.errors.extend(result.errors)
+
+        loaded: Dict[str, Mod] = {}
+        for info in result.mods:
+            mod = Mod.load(self, info)
+            mod.disabled = disabled
+            if info.nested_in is not None:
+                mod.parent = loaded.get(info.nested_in, None)
+            loaded[info.file] = mod
+            self.register(mod)
+
+        return result.found
+
+    # `jobs` caps the number of worker processes, 1 scans in this process
+    def load(
+                self,
+                jobs: Optional[int] = None,
+                cache: Optional[ScanCache] = None,
+                progress: bool = True
+            ) -> bool:
         mod_dir = DirectoryReal(self.directory, 'mods')
         # for file in self.directory.list():
-        for file in tqdm(mod_dir.list()):
-            if not issubclass(type(file), FileBase):
+        files = sorted(
+            [
+                x.name for x in mod_dir.list()
+                if issubclass(type(x), FileBase)
+                and x.name.endswith(('.jar', '.jar.disabled'))
+            ]
+        )
+        paths = [os.path.join(mod_dir.full_path, x) for x in files]
+
+        cached: Dict[str, ScanResult] = {}
+        if cache is not None:
+            for path in paths:
+                result = cache.get(path, self.loader)
+                if result is not None:
+                    cached[path] = result
+        pending = [x for x, y in zip(files, paths) if y not in cached]
+        log.info(
+            f'{len(files)} jars in {mod_dir.full_path}, '
+            f'{len(cached)} cached, {len(pending)} to scan'
+        )
+
+        scan = partial(scan_file, mod_dir.full_path, loader=self.loader)
+        # cached jars count as scanned already
+        bar = tqdm(
+            total=len(files),
+            initial=len(files) - len(pending),
+            unit='jar',
+            desc='scanning',
+            disable=not progress
+        )
+        with bar:
+            if jobs == 1 or len(pending) <= 1:
+                scanned = []
+                for filename in pending:
+                    scanned.append(scan(filename))
+                    bar.update()
+            else:
+                with ProcessPoolExecutor(max_workers=jobs) as executor:
+                    scanned = []
+                    # results arrive in order, as workers finish them
+                    for result in executor.map(scan, pending, chunksize=8):
+                        scanned.append(result)
+                        bar.update()
+
+        for filename, result in zip(pending, scanned):
+            cached[os.path.join(mod_dir.full_path, filename)] = result
+            if cache is not None:
+                cache.put(
+                    os.path.join(mod_dir.full_path, filename),
+                    result,
+                    self.loader
+                )
+        if cache is not None:
+            cache.prune(paths)
+
+        # registered in the order of `files`, keeping reports stable
+        results = [cached[x] for x in paths]
+        for filename, result in zip(files, results):
+            found = self.process_jar(result, filename.endswith('.disabled'))
+            if not found:
+                self.errors.append(
+                    f"Failed to locate mod in jar '{filename}'"
+                )
+                # return False
+        return True
+
+    # top-level jars providing each modid, for modids with several active ones
+    def duplicates(self) -> Dict[str, List[Mod]]:
+        groups: Dict[str, List[Mod]] = {}
+        for mod in self.all_mods:
+            if not mod.nested:
+                groups.setdefault(mod.modid, []).append(mod)
+
+        return {
+            modid: mods for modid, mods in groups.items()
+            if len([x for x in mods if x.active]) > 1
+        }
+
+    def duplicate_report(self, modid: str) -> Optional[DuplicateReport]:
+        mods = self.duplicates().get(modid, None)
+        if mods is None:
+            return None
+        return DuplicateReport(modid, [
+            DuplicateFileReport(x.filename, str(x._version), x.disabled)
+            for x in mods
+        ])
+
+    # makes dependencies on `modid` resolve against `version`, adding a
+    # stand-in for the mod when it isn't installed
+    def override(self, modid: str, version: Version) -> None:
+        if modid not in self.mods:
+            mod = Mod(self)
+            mod.filename = '[no file]'
+            mod.name = modid
+            mod.modid = modid
+            self.mods[modid] = mod
+        self.mods[modid]._version = version
+        self.mods[modid].any_override = version.text == '*'
+
+    # a disabled mod, or one nested in a jar that lost to a top-level one,
+    # that would satisfy `dep` when what's loaded doesn't
+    def inactive_candidate(
+                self,
+                dep: ModDependency
+            ) -> Optional[CandidateReport]:
+        provider = self.provider(dep.modid)
+        candidates = [
+            x for x in self.all_mods
+            if x is not provider and (not x.active or x.nested)
+            and x.info is not None and dep.validateMod(x)
+        ]
+        # top-level disabled jars are the easiest to fix
+        candidates.sort(key=lambda x: (x.active, x.nested))
+        if len(candidates) == 0:
+            return None
+
+        candidate = candidates[0]
+        return CandidateReport(
+            candidate.modid,
+            str(candidate._version),
+            candidate.jar_file,
+            not candidate.active
+        )
+
+    # `, <modid> <version> is present but disabled (...)` for errors
+    def candidate_note(self, dep: ModDependency) -> str:
+        candidate = self.inactive_candidate(dep)
+        return f', {candidate.text()}' if candidate is not None else ''
+
+    def dependency_report(
+                self,
+                mod: Mod,
+                dep: ModDependency
+            ) -> DependencyReport:
+        provider = self.provider(dep.modid)
+        if mod.lie_depends:
+            satisfied = True
+        elif provider is None:
+            satisfied = not dep.required
+        else:
+            satisfied = dep.validateMod(provider)
+
+        return DependencyReport(
+            dep.modid,
+            dep.required,
+            [str(x) for x in dep.version_reqs],
+            provider is not None,
+            str(provider._version) if provider is not None else None,
+            satisfied,
+            provider.jar_file if provider is not None else None,
+            provider.modid if provider is not None
+            and provider.modid != dep.modid else None,
+            self.inactive_candidate(dep) if not satisfied else None
+        )
+
+    # installed mods in a version `mod` declares it can't be used with
+    def conflict_reports(self, mod: Mod) -> List[ConflictReport]:
+        reports: List[ConflictReport] = []
+        for conflict in mod.conflicts:
+            other = self.mods.get(conflict.modid, None)
+            if other is None or other.any_version:
                 continue
-            file = cast(FileBase, file)
-            if file.name.endswith('.disabled'):
+            if not conflict.validateMod(other):
                 continue
+            reports.append(ConflictReport(
+                mod.modid,
+                conflict.modid,
+                [str(x) for x in conflict.version_reqs],
+                str(other._version),
+                conflict.kind == 'breaks'
+            ))
+        return reports
+
+    def mod_report(self, mod: Mod) -> ModReport:
+        return ModReport(
+            mod.modid,
+            mod.name,
+            str(mod._version),
+            mod.loader,
+            mod.filename,
+            mod.lie_depends,
+            [self.dependency_report(mod, dep) for dep in mod.dependencies],
+            mod.jar_file if mod.nested else None,
+            mod.java_version,
+            mod.disabled,
+            list(mod.provides),
+            mod.environment
+        )
 
-            with ZipFile(
-                        os.path.join(mod_dir.full_path, file.name),
-                        'r'
-                    ) as jar:
-                result = self.process_jar(
-                    DirectoryZip(mod_dir, file.name, jar)
+    # flags mods and dependencies that can't work on `side`
+    def validateSides(self, side: str) -> None:
+        other = 'server' if side == 'client' else 'client'
+        for mod in self.mods.values():
+            if mod.environment == other:
+                mod.errors.append(
+                    f"'{mod.modid}' is {other}-only and can't run on the "
+                    f"{side}"
                 )
-                if not result:
-                    self.errors.append(
-                        f"Failed to locate mod in jar '{file.name}'"
+                continue
+
+            for dep in mod.dependencies:
+                dependency = self.provider(dep.modid)
+                if not dep.required or dependency is None:
+                    continue
+                if dep.side.lower() == other:
+                    continue
+                if dependency.environment == other:
+                    mod.errors.append(
+                        f"'{mod.modid}' requires '{dep.modid}' on the "
+                        f"{side}, but '{dep.modid}' is {other}-only"
                     )
-                    # return False
-        return True
 
-    def validateVersions(self, verbose: bool) -> bool:
+    def validateJava(self, java: int) -> None:
+        version = Version.fromString(str(java))
         for mod in self.mods.values():
+            if mod.java_version is not None and mod.java_version > java:
+                mod.errors.append(
+                    f"'{mod.modid}' needs java {mod.java_version}, but the "
+                    f"profile runs on java {java}"
+                )
+            for dep in mod.java_reqs:
+                if dep.required and not any(
+                            [x.contains(version) for x in dep.version_reqs]
+                        ):
+                    mod.errors.append(
+                        f"'{mod.modid}' supports java {dep.version_reqs}, "
+                        f"but the profile runs on java {java}"
+                    )
+
+    # mods made for another minecraft version, or a warning when the
+    # version of the profile is unknown
+    def validateMinecraft(self) -> List[str]:
+        if 'minecraft' not in self.mods:
+            declared = [
+                x for x in self.mods.values()
+                if any([y.modid == 'minecraft' for y in x.dependencies])
+            ]
+            if len(declared) == 0:
+                return []
+            return [
+                f'the minecraft version of the profile is unknown, '
+                f'{len(declared)} mods depending on it were not checked. '
+                f'provide it with `--override-versions minecraft=<version>`'
+            ]
+
+        minecraft = self.mods['minecraft']
+        for mod in self.mods.values():
+            if mod.lie_depends:
+                continue
+            for dep in mod.dependencies:
+                if dep.modid == 'minecraft' and not dep.validateMod(minecraft):
+                    mod.errors.append(
+                        f"'{mod.modid}' supports minecraft "
+                        f"{dep.version_reqs}, but the profile runs "
+                        f"{minecraft._version}"
+                    )
+        return []
+
+    # installed pairs of mods from the incompatibility database
+    def incompatibility_reports(
+                self,
+                incompatibilities: List[Incompatibility]
+            ) -> List[IncompatibilityReport]:
+        reports: List[IncompatibilityReport] = []
+        for entry in incompatibilities:
+            installed: List[Mod] = []
+            for modid in entry.mods:
+                mod = self.provider(modid)
+                if mod is None or mod.any_version:
+                    break
+                ranges = entry.versions.get(modid, None)
+                if ranges is not None and not any(
+                            [x.contains(mod._version) for x in ranges]
+                        ):
+                    break
+                installed.append(mod)
+            else:
+                reports.append(IncompatibilityReport(
+                    installed[0].modid,
+                    installed[1].modid,
+                    entry.reason,
+                    entry.source,
+                    entry.severity
+                ))
+        reports.sort(key=lambda x: (x.modid, x.incompatible_with))
+        return reports
+
+    # libraries in the mods folder nothing depends on, either marked as one
+    # or named like one without content of their own. other mods without
+    # dependents are what the pack is made of instead
+    def orphan_reports(
+                self,
+                dependency_graph: graph.DependencyGraph
+            ) -> List[OrphanReport]:
+        reports: List[OrphanReport] = []
+        for mod in sorted(self.mods.values(), key=lambda x: x.modid):
+            # bundled libraries are the business of the jar they're in
+            if mod.info is None or mod.nested:
+                continue
+            if len(dependency_graph.dependents.get(mod.modid, [])) > 0:
+                continue
+            named = is_library_name(mod.modid, mod.name)
+            if mod.info.library or (named and not mod.info.content):
+                reports.append(
+                    OrphanReport(mod.modid, mod.jar_file, mod.info.library)
+                )
+        return reports
+
+    # missing required mods, ordered so the ones others require come first.
+    # only the dependencies of disabled jars are known, the rest come last
+    def install_order(self, side: Optional[str] = None) -> InstallOrderReport:
+        other_side = {'client': 'server', 'server': 'client'}.get(side, None)
+        disabled = self.disabled_mods()
+
+        steps: Dict[str, InstallStepReport] = {}
+        queue = [
+            x for x in sorted(self.mods.values(), key=lambda x: x.modid)
+            if not x.lie_depends
+        ]
+        while len(queue) > 0:
+            mod = queue.pop(0)
             for dep in mod.dependencies:
-                if dep.modid in self.mods:
-                    dependency = self.mods[dep.modid]
-                    if not dep.validateMod(dependency):
+                if not dep.required or dep.modid == 'minecraft':
+                    continue
+                if dep.side.lower() == other_side:
+                    continue
+                if self.provider(dep.modid) is not None:
+                    continue
+                if dep.modid not in steps:
+                    steps[dep.modid] = InstallStepReport(dep.modid)
+                    if dep.modid in disabled:
+                        steps[dep.modid].file = disabled[dep.modid].filename
+                        queue.append(disabled[dep.modid])
+                if mod.modid not in steps[dep.modid].requested_by:
+                    steps[dep.modid].requested_by.append(mod.modid)
+                if mod.modid in steps:
+                    steps[mod.modid].after.append(dep.modid)
+
+        report = InstallOrderReport()
+        report.unknown = [
+            steps[x] for x in sorted(steps.keys()) if x not in disabled
+        ]
+        # the ones with all their known requirements placed go next, and
+        # what's left of a cycle after that
+        known = [x for x in sorted(steps.keys()) if x in disabled]
+        while len(known) > 0:
+            placed = [x.modid for x in report.steps]
+            ready = [
+                x for x in known
+                if all([y in placed or y not in disabled for y in
+                        steps[x].after])
+            ] or known[:1]
+            report.steps.extend([steps[x] for x in ready])
+            known = [x for x in known if x not in ready]
+        return report
+
+    # recommended and suggested mods that aren't installed, each listed once
+    # with the mods wanting it
+    def recommendation_reports(self) -> List[RecommendationReport]:
+        reports: Dict[str, RecommendationReport] = {}
+        for mod in sorted(self.mods.values(), key=lambda x: x.modid):
+            for dep in mod.dependencies:
+                if dep.kind not in ['recommends', 'suggests']:
+                    continue
+                if self.provider(dep.modid) is not None:
+                    continue
+                report = reports.setdefault(
+                    dep.modid,
+                    RecommendationReport(dep.modid)
+                )
+                report.requested_by.setdefault(mod.modid, dep.kind)
+        return [reports[x] for x in sorted(reports.keys())]
+
+    # `side` checks that mods can run on the `client` or `server`, `java`
+    # that they can run on that java version, `mixins` and `access` report
+    # classes and members changed by several mods, `orphans` libraries
+    # nothing depends on and `recommendations` the missing mods others
+    # recommend. `incompatibilities` are pairs of mods known not to work
+    # together, and `install_order` orders the missing mods
+    def validateVersions(
+                self,
+                side: Optional[str] = None,
+                java: Optional[int] = None,
+                mixins: bool = False,
+                orphans: bool = False,
+                access: bool = False,
+                recommendations: bool = False,
+                incompatibilities: Optional[List[Incompatibility]] = None,
+                install_order: bool = False
+            ) -> ValidateReport:
+        other_side = {'client': 'server', 'server': 'client'}.get(side, None)
+        if side is not None:
+            self.validateSides(side)
+        if java is not None:
+            self.validateJava(java)
+        warnings = self.validateMinecraft()
+
+        for mod in self.mods.values():
+            lied = mod.lie_depends
+            # minecraft is left to `validateMinecraft`
+            for dep in mod.dependencies:
+                minecraft = dep.modid == 'minecraft'
+                dependency = self.provider(dep.modid)
+                if dependency is not None:
+                    log.log(
+                        TRACE,
+                        f"'{mod.modid}' -> '{dep.modid}' {dep.version_reqs}: "
+                        f"found '{dependency.modid}' {dependency._version}"
+                    )
+                    valid = dep.validateMod(dependency)
+                    if not valid and not lied and not minecraft:
+                        alias = ''
+                        if dependency.modid != dep.modid:
+                            alias = f" of '{dep.modid}', which it provides"
                         dependency.errors.append(
                             f"'{mod.modid}' requires '{dep.version_reqs}'"
+                            f"{alias}{self.candidate_note(dep)}"
                         )
 
                     rdep_mod = ModDependency(mod.modid, False, '*')
@@ -316,215 +737,272 @@ class ModPack:
                     dependency.dependents.append(rdep_mod)
 
                 else:
-                    if dep.required and dep.modid not in []:
+                    # only needed on the side that isn't being checked
+                    off_side = dep.side.lower() == other_side
+                    log.log(
+                        TRACE,
+                        f"'{mod.modid}' -> '{dep.modid}' {dep.version_reqs}: "
+                        f"not installed"
+                        + (', lied to' if lied else '')
+                        + (', other side' if off_side else '')
+                    )
+                    needed = dep.required and not off_side
+                    if needed and not lied and not minecraft:
                         mod.errors.append(
                             f"Could not find mod '{dep.modid}'! "
                             f"requirements: {dep.version_reqs}"
+                            f"{self.candidate_note(dep)}"
                         )
 
-        err_num = 0
-        for mod in self.mods.values():
-            if len(mod.errors) > 0:
-                err_num += 1
-                if verbose:
-                    print(f'{mod.name} ({mod.modid}) {mod._version}:')
-                    print(f' ->  [file]: {mod.filename}')
-                    for error in mod.errors:
-                        print(f' --> {error}')
-                    print()
-
-        for error in self.errors:
-            err_num += 1
-            if verbose:
-                print(f' -> {error}')
-
-        return err_num == 0
-
-    def why_depends(self, modid: str, error: bool) -> None:
-        if modid not in self.mods:
-            print('==================================')
-            print(f'why-depends: modid "{modid}" not found!\n')
-            return
-
-        mod = self.mods[modid]
-        print(f'{mod.name} ({modid}) [{mod._version}]:')
-        print(f' -> File: "{mod.filename}"\n')
-        print(f' -> Dependencies')
-        for dep in mod.dependencies:
-            vers_reqs_met = any(
-                [range.contains(mod._version) for range in dep.version_reqs]
-            )
-            if not error or (error and not vers_reqs_met):
-                dep_mod = self.mods.get(dep.modid, None)
-                dep_name = dep_mod.name if dep_mod else dep.modid
-                dep_installed = dep.modid in self.mods
-                print(f'   -> name:      {dep_name}')
-                print(f'   -> modid:     {dep.modid}')
-                print(f'   -> required:  {"yes" if dep.required else "no"}')
-                print(f'   -> installed: {"yes" if dep_installed else "no"}')
-                print(f'   -> versions:  {dep.version_reqs}')
-                print()
-
-        print(f' -> Dependents')
-        for dep in mod.dependents:
-            vers_reqs_met = any(
-                [range.contains(mod._version) for range in dep.version_reqs]
+        report = ValidateReport(
+            True,
+            errors=list(self.errors),
+            warnings=warnings
+        )
+        for modid in sorted(self.duplicates().keys()):
+            report.duplicates.append(
+                cast(DuplicateReport, self.duplicate_report(modid))
             )
-            if not error or (error and not vers_reqs_met):
-                dep_mod = self.mods.get(dep.modid, None)
-                dep_name = dep_mod.name if dep_mod else dep.modid
-                dep_installed = dep.modid in self.mods
-                print(f'   -> name:      {dep_name}')
-                print(f'   -> modid:     {dep.modid}')
-                # print(f'   -> required: {dep.required}')
-                print(f'   -> installed: {"yes" if dep_installed else "no"}')
-                print(f'   -> versions:  {dep.version_reqs}')
-                print()
-
-    def run(self) -> bool:
-        return False
 
-    def identifyBrokenMods(self, error: str) -> bool:
-        graphs: List[DependencyGraph] = []
+        for mod in sorted(self.mods.values(), key=lambda x: x.modid):
+            report.conflicts.extend(self.conflict_reports(mod))
+        report.incompatibilities = self.incompatibility_reports(
+            incompatibilities or []
+        )
 
-        for mod in self.mods.values():
-            if mod.modid in ['minecraft', 'forge']:
-                continue
-            graph = DependencyGraph(mod)
-            graphs.append(graph)
-            DependencyGraph._ALL_NODES[mod.modid] = graph.nodes[0]
-            DependencyGraph._ALL_GRAPHS[mod.modid] = graph
-
-        # TODO: Merge circular node paths into a single node each
-
-        def process_graph(graph: DependencyGraph):
-            for node in graph.nodes:
-                for dep in node.dependents:
-                    invalid_modid = dep.modid in ['minecraft', 'forge']
-                    not_installed = dep.modid not in self.mods
-                    if invalid_modid or (not dep.required and not_installed):
-                        continue
-                    mod_graph = DependencyGraph._ALL_GRAPHS[mod.modid]
-                    dep_graph = DependencyGraph._ALL_GRAPHS[dep.modid]
-                    if mod_graph is not dep_graph:
-                        mod_graph.merge(dep_graph)
-                        process_graph(dep_graph)
-                for dep in node.dependencies:
-                    invalid_modid = dep.modid in ['minecraft', 'forge']
-                    not_installed = dep.modid not in self.mods
-                    if invalid_modid or (not dep.required and not_installed):
-                        continue
-                    mod_graph = DependencyGraph._ALL_GRAPHS[mod.modid]
-                    dep_graph = DependencyGraph._ALL_GRAPHS[dep.modid]
-                    if mod_graph is not dep_graph:
-                        mod_graph.merge(dep_graph)
-                        process_graph(dep_graph)
+        if mixins:
+            report.mixin_overlaps = find_overlaps({
+                x.modid: x.info.mixins for x in self.mods.values()
+                if x.info is not None
+            })
+        if access:
+            report.access_overlaps = find_access_overlaps({
+                x.modid: x.info.access for x in self.mods.values()
+                if x.info is not None
+            })
+
+        dependency_graph = graph.DependencyGraph(self.mods, self.aliases)
+        for cycle in dependency_graph.find_cycles():
+            report.cycles.append(CycleReport(
+                cycle,
+                dependency_graph.is_mandatory_cycle(cycle)
+            ))
+        if orphans:
+            report.orphans = self.orphan_reports(dependency_graph)
+        if recommendations:
+            report.recommendations = self.recommendation_reports()
+        if install_order:
+            report.install_order = self.install_order(side)
 
         for mod in self.mods.values():
-            if mod.modid in ['minecraft', 'forge']:
+            unsatisfied = [
+                x for x, dep in zip(
+                    self.mod_report(mod).dependencies,
+                    mod.dependencies
+                )
+                if not x.satisfied and dep.side.lower() != other_side
+                and dep.modid != 'minecraft'
+            ]
+            if len(mod.errors) > 0 or len(unsatisfied) > 0:
+                report.mods.append(
+                    ModErrorsReport(
+                        mod.modid,
+                        mod.name,
+                        str(mod._version),
+                        mod.filename,
+                        list(mod.errors),
+                        unsatisfied
+                    )
+                )
+
+        report.passed = (
+            len(report.mods) == 0 and
+            len(report.errors) == 0 and
+            len(report.duplicates) == 0 and
+            len(report.hard_conflicts()) == 0 and
+            all([x.severity != 'error' for x in report.incompatibilities])
+        )
+        report.severity = report.find_severity()
+
+        loaders: Dict[str, int] = {}
+        for mod in sorted(self.mods.values(), key=lambda x: x.loader or ''):
+            if mod.info is not None:
+                loader = mod.info.loader
+                loaders[loader] = loaders.get(loader, 0) + 1
+        report.summary = report.summarize(loaders)
+        return report
+
+    # marks the mods matching the glob patterns so their dependencies are
+    # treated as satisfied, returning the modids each pattern matched
+    def lie_depends(self, patterns: Set[str]) -> Dict[str, List[str]]:
+        matches: Dict[str, List[str]] = {}
+        for pattern in sorted(patterns):
+            matches[pattern] = [
+                x for x in sorted(self.mods.keys())
+                if fnmatch.fnmatchcase(x, pattern)
+            ]
+            for modid in matches[pattern]:
+                self.mods[modid].lie_depends = True
+        return matches
+
+    # installed and disabled modids, and those mods depend on
+    def known_modids(self) -> Set[str]:
+        modids = set(self.mods.keys())
+        for mod in self.all_mods:
+            modids.add(mod.modid)
+            modids.update([x.modid for x in mod.dependencies])
+        return modids
+
+    def closest_modids(self, modid: str) -> List[str]:
+        return [x for x, _ in closest_modids(modid, self.known_modids())]
+
+    # top-level disabled jars of modids without an active one
+    def disabled_mods(self) -> Dict[str, Mod]:
+        disabled: Dict[str, Mod] = {}
+        for mod in self.all_mods:
+            if mod.active or mod.modid in self.mods:
                 continue
-            process_graph(DependencyGraph._ALL_GRAPHS[mod.modid])
-
-        graph_list: List[DependencyGraph] = []
-        for node in DependencyGraph._ALL_NODES.values():
-            if node.graph not in graph_list:
-                graph_list.append(node.graph)
-        # sort by number of mods in graph
-        graph_list = sorted(
-            graph_list,
-            key=(lambda x: sum([len(y.mod_set) for y in x.nodes]))
+            if mod.modid not in disabled or disabled[mod.modid].nested:
+                disabled[mod.modid] = mod
+        return disabled
+
+    # dependencies of `mod` and theirs in turn, the ones of a mod are only
+    # listed the first time it appears
+    def dependency_tree(
+                self,
+                mod: Mod,
+                shown: Set[str]
+            ) -> List[DependencyTreeReport]:
+        shown.add(mod.modid)
+        tree: List[DependencyTreeReport] = []
+        for dep in mod.dependencies:
+            node = DependencyTreeReport(self.dependency_report(mod, dep))
+            provider = self.provider(dep.modid)
+            if provider is not None and provider.modid in shown:
+                node.repeated = len(provider.dependencies) > 0
+            elif provider is not None:
+                node.dependencies = self.dependency_tree(provider, shown)
+            tree.append(node)
+        return tree
+
+    # `deps` adds the transitive dependencies of `modid`
+    def mod_info(
+                self,
+                modid: Optional[str],
+                deps: bool = False
+            ) -> ModInfoReport:
+        mods = dict(self.disabled_mods())
+        mods.update(self.mods)
+
+        if modid is None:
+            return ModInfoReport([
+                self.mod_report(mod)
+                for mod in sorted(mods.values(), key=lambda x: x.modid)
+            ])
+
+        if modid not in mods:
+            report = ModInfoReport([], modid)
+            report.suggestions = self.closest_modids(modid)
+            return report
+
+        # the one in use first, then every other jar with the modid
+        matches = [mods[modid]] + [
+            x for x in self.all_mods
+            if x.modid == modid and x is not mods[modid]
+        ]
+        report = ModInfoReport(
+            [self.mod_report(x) for x in matches],
+            modid,
+            self.duplicate_report(modid)
         )
-
-        for i, graph in enumerate(graph_list):
-            print('==================================')
-            mod_count = sum([len(y.mod_set) for y in graph.nodes])
-            print(f'Graph {i} ({mod_count} mods):')
-            for node in graph.nodes:
-                for mod in node.mod_set:
-                    print(f" -> '{mod.modid}'")
-            print()
-
-        print(f'total loaded mods: {len(self.mods)}')
-        print(f'total separate graphs: {len(graph_list)}')
-
-        missing_count = 0
-        for modid in self.mods.keys():
-            invalid_modid = modid in ['minecraft', 'forge']
-            mod_installed = modid in DependencyGraph._ALL_NODES.keys()
-            if not mod_installed and not invalid_modid:
-                missing_count += 1
-                mod_name = self.mods[modid].name
-                print(f'Missing graph for mod "{mod_name}" ({modid})')
-        print(f'Missing graphs for {missing_count} mods')
-
-        logs = DirectoryReal(self.directory, "logs")
-
-        error_files = ['latest.log', 'debug.log', 'latest_stdout.log']
-        search_filename = ''
-        for candidate in error_files:
-            log_exists = logs.has(candidate)
-            log = FileReal(logs, candidate)
-            log_has_error = error in log.read().decode(errors="ignore")
-            if log_exists and log_has_error:
-                search_filename = candidate
-
-        if search_filename:
-            print(f'Scanning "{search_filename}"')
-        else:
-            print('Scanning all')
-
-        # TODO: Determine which graph is causing the provided error
-        # by disabling half the remaining graphs each time until a
-        # single graph is left
-
-        # find the only True value in the list
-        # number of iterations = int(ceil(log2(len(graph_list))))
-        def binaryGraphElimination(_list: List[DependencyGraph]) -> int:
-            __list = [DependencyGraph(Mod(self))] + _list
-            left = 0
-            right = len(__list) - 1
-
-            while left <= right:
-                mid = (left + right) // 2
-                # TODO: Disable graphs in __list[mid:]
-                for graph in __list[mid:]:
-                    graph.disable_all()
-                result = self.run()  # return True if run occurs successfully
-                if any(__list[mid:]):
-                    left = mid + 1
-                else:
-                    right = mid - 1
-
-            return right - 1
-
-        # bad_graph1 = binaryGraphElimination(graph_list)
-        # bad_graph2 = None
-
-        # if bad_graph1 >= 0:
-        #     graph_list.remove(bad_graph1)
-        #     bad_graph2 = binaryElimination(graph_list)
-        # else:
-        #     ... # no error??
-
-        # if bad_graph2 >= 0:
-        #     bad_mod1 = find_bad_mod(bad_graph1)
-        #     bad_mod2 = find_bad_mod(bad_graph2)
-        #     report_mod_conflict(bad_mod1, bad_mod2)
-        # else:
-        #     bad_mod1 = find_bad_mod(bad_graph1)
-        #     bad_mod2 = None
-        #     if bad_mod1 >= 0:
-        #         bad_graph1.freeze(bad_mod1)
-        #         bad_mod2 = find_bad_mod(bad_graph1)
-        #         if bad_mod2:
-        #             report_mod_conflict(bad_mod1, bad_mod2)
-        #         else:
-        #             report_bad_mod(bad_mod1)
-        #     else:
-        #         ... # no error??
-
-        # TODO: Sort the remaining graph, then disable the leaves,
-        # and iterate to each of the parents if all of that parent's
-        # children have been visited
-
-        return True
+        if deps:
+            report.tree = self.dependency_tree(mods[modid], set())
+        return report
+
+    def why_depends(
+                self,
+                modid: str,
+                error: bool,
+                resolver: Optional['ModrinthResolver'] = None
+            ) -> WhyDependsReport:
+        dependency_graph = graph.DependencyGraph(self.mods, self.aliases)
+        mod = self.provider(modid)
+        report = WhyDependsReport(modid, mod is not None, errors_only=error)
+
+        # dependencies on a provided modid count towards the providing mod
+        target = dependency_graph.resolve(modid)
+        if mod is None and target not in dependency_graph.dependents:
+            report.found = False
+            report.suggestions = self.closest_modids(modid)
+            return report
+
+        if mod is not None:
+            report.name = mod.name
+            report.version = str(mod._version)
+            report.file = mod.filename
+            if mod.nested:
+                report.provided_by = mod.jar_file
+            if mod.modid != modid:
+                report.satisfied_by = mod.modid
+
+        chains, report.truncated = dependency_graph.reverse_dependents(target)
+        for chain in chains:
+            unsatisfied = dependency_graph.unsatisfied_edges(chain)
+            if error and len(unsatisfied) == 0:
+                continue
+            chain_report = ChainReport(chain)
+            for dependent, dep in unsatisfied:
+                edge = UnsatisfiedEdgeReport(
+                    dependent,
+                    dep.modid,
+                    [str(x) for x in dep.version_reqs],
+                    self.provider(dep.modid) is not None
+                )
+                candidate = self.inactive_candidate(dep)
+                if candidate is not None and candidate.disabled:
+                    edge.disabled_provider = candidate.file
+                if resolver is not None and error and dep.required:
+                    edge.suggestion = resolver.suggest(dep)
+                chain_report.unsatisfied.append(edge)
+            report.chains.append(chain_report)
+
+        return report
+
+    def load_order(self) -> LoadOrderReport:
+        # mods provided by `--override-versions` have no jar to order
+        roots = [x.modid for x in self.mods.values() if x.info is None]
+        dependency_graph = graph.DependencyGraph(self.mods, self.aliases)
+        order, conflicts = dependency_graph.load_order(roots)
+        return LoadOrderReport(order, roots, conflicts)
+
+
+def test():
+    from metadata import ForgeMetadata, Dependency
+
+    pack = ModPack(DirectoryReal(None, 'test'), 'forge')
+    for info in [
+                ForgeMetadata(
+                    'a.jar', 'a', '1.0.0', 'A', 'forge',
+                    [Dependency('b', True, '[2.0,)')]
+                ),
+                ForgeMetadata('b.jar', 'b', '1.5.0', 'B', 'forge'),
+            ]:
+        pack.register(Mod.load(pack, info))
+    dep = pack.mods['a'].dependencies[0]
+
+    print('===========================================================')
+    print(pack.mods['b']._version, dep.version_reqs)
+    print('[PASS]' if not dep.validateMod(pack.mods['b']) else '[FAIL]')
+    print('===========================================================')
+    # `b=*` accepts any version, even with a jar providing it
+    pack.override('b', Version.fromString('*'))
+    print(pack.mods['b']._version, dep.version_reqs)
+    print('[PASS]' if dep.validateMod(pack.mods['b']) else '[FAIL]')
+    print('===========================================================')
+    pack.override('b', Version.fromString('1.6.0'))
+    print(pack.mods['b']._version, dep.version_reqs)
+    print('[PASS]' if not dep.validateMod(pack.mods['b']) else '[FAIL]')
+
+
+if __name__ == '__main__':
+    test()
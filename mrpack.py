@@ -0,0 +1,162 @@
+
+from attrs import define
+import toml
+
+from typing import Any, Dict, Optional
+from zipfile import ZipFile, ZIP_DEFLATED
+from urllib.parse import urlparse
+import json
+import os
+
+from filesystem import FileBase, FileReal, DirectoryReal
+from mod_info import ModPack
+from output import ExportReport
+
+
+INDEX_FILE = 'modrinth.index.json'
+
+# the only hosts modrinth accepts in `downloads`
+ALLOWED_HOSTS = [
+    'cdn.modrinth.com',
+    'github.com',
+    'raw.githubusercontent.com',
+    'gitlab.com',
+]
+
+# modids of loaders and the names modrinth uses for them
+LOADER_DEPENDENCIES: Dict[str, str] = {
+    'minecraft': 'minecraft',
+    'forge': 'forge',
+    'neoforge': 'neoforge',
+    'fabricloader': 'fabric-loader',
+    'quilt_loader': 'quilt-loader',
+}
+
+# packwiz `side` values mapped to the `env` of an index entry
+SIDE_ENV: Dict[str, Dict[str, str]] = {
+    'client': {'client': 'required', 'server': 'unsupported'},
+    'server': {'client': 'unsupported', 'server': 'required'},
+    'both': {'client': 'required', 'server': 'required'},
+}
+
+
+class ExportError(Exception):
+    ...
+
+
+@define
+class Download:
+    url:            str
+    side:           str = 'both'
+    # the hash recorded when the mod was downloaded, eg: `sha1`
+    hash_format:    Optional[str] = None
+    hash:           Optional[str] = None
+
+
+# launchers like prism keep packwiz metadata of managed mods in `.index`
+def read_downloads(mod_dir: DirectoryReal) -> Dict[str, Download]:
+    downloads: Dict[str, Download] = {}
+
+    index_dir = os.path.join(mod_dir.full_path, '.index')
+    if not os.path.isdir(index_dir):
+        return downloads
+
+    for name in sorted(os.listdir(index_dir)):
+        if not name.endswith('.pw.toml'):
+            continue
+        try:
+            with open(os.path.join(index_dir, name), 'r') as file:
+                data: Dict[str, Any] = toml.loads(file.read())
+            download = data['download']
+            downloads[data['filename']] = Download(
+                download['url'],
+                data.get('side', 'both'),
+                download.get('hash-format', None),
+                download.get('hash', None)
+            )
+        except (OSError, KeyError, TypeError, toml.TomlDecodeError):
+            continue
+
+    return downloads
+
+
+def is_allowed_url(url: str) -> bool:
+    return urlparse(url).hostname in ALLOWED_HOSTS
+
+
+def pack_dependencies(pack: ModPack) -> Dict[str, str]:
+    dependencies: Dict[str, str] = {}
+    for modid, name in LOADER_DEPENDENCIES.items():
+        if modid not in pack.mods:
+            continue
+        if pack.mods[modid].any_version:
+            raise ExportError(
+                f'export: the pack needs an exact {modid} version, '
+                f'not `{modid}=*`'
+            )
+        dependencies[name] = str(pack.mods[modid]._version)
+
+    if 'minecraft' not in dependencies:
+        raise ExportError(
+            'export: the minecraft version of the profile is unknown, '
+            'provide it with `--override-versions minecraft=<version>`'
+        )
+    return dependencies
+
+
+def export_mrpack(
+            pack: ModPack,
+            output: str,
+            name: str,
+            version: str
+        ) -> ExportReport:
+    report = ExportReport(output, pack_dependencies(pack))
+
+    mod_dir = DirectoryReal(pack.directory, 'mods')
+    downloads = read_downloads(mod_dir)
+    jars = sorted(
+        [
+            x for x in mod_dir.list()
+            if issubclass(type(x), FileBase) and x.name.endswith('.jar')
+        ],
+        key=lambda x: x.name
+    )
+
+    index: Dict[str, Any] = {
+        'formatVersion': 1,
+        'game': 'minecraft',
+        'versionId': version,
+        'name': name,
+        'files': [],
+        'dependencies': report.dependencies,
+    }
+
+    with ZipFile(output, 'w', ZIP_DEFLATED) as mrpack:
+        for jar in jars:
+            jar = FileReal(mod_dir, jar.name)
+            path = f'mods/{jar.name}'
+            download = downloads.get(jar.name, None)
+
+            hashes = {x: jar.hash(x) for x in ['sha1', 'sha512']}
+            # the file changed since it was downloaded
+            if download is not None and download.hash_format in hashes:
+                if hashes[download.hash_format] != download.hash:
+                    download = None
+
+            if download is None or not is_allowed_url(download.url):
+                mrpack.write(jar.full_path, f'overrides/{path}')
+                report.overrides.append(path)
+                continue
+
+            index['files'].append({
+                'path': path,
+                'hashes': hashes,
+                'env': SIDE_ENV.get(download.side, SIDE_ENV['both']),
+                'downloads': [download.url],
+                'fileSize': len(jar),
+            })
+            report.files.append(path)
+
+        mrpack.writestr(INDEX_FILE, json.dumps(index, indent=2))
+
+    return report
@@ -0,0 +1,1179 @@
+
+from attrs import define, Factory, asdict
+
+from typing import Any, Dict, List, Optional
+import json
+import sys
+import os
+
+
+FORMATS = ['text', 'json']
+# mods listed under the most unsatisfied dependencies of a summary
+SUMMARY_TOP = 5
+COLOR_MODES = ['auto', 'always', 'never']
+# from least to most severe
+SEVERITIES = ['info', 'warning', 'error']
+
+ANSI_CODES: Dict[str, str] = {
+    'red': '31',
+    'green': '32',
+    'yellow': '33',
+    'cyan': '36',
+}
+SEVERITY_COLORS: Dict[str, str] = {
+    'info': 'cyan',
+    'warning': 'yellow',
+    'error': 'red',
+}
+
+# set by `use_color`, reports are plain text until then
+_color = False
+
+
+# `auto` colors text for terminals, unless `NO_COLOR` is set
+def use_color(mode: str, format: str) -> None:
+    global _color
+    if mode == 'auto':
+        _color = (
+            format == 'text'
+            and sys.stdout.isatty()
+            and os.environ.get('NO_COLOR', '') == ''
+        )
+    else:
+        _color = mode == 'always'
+
+
+def paint(text: str, color: str) -> str:
+    if not _color:
+        return text
+    return f'\033[{ANSI_CODES[color]}m{text}\033[0m'
+
+
+# eg: `[warning]`
+def severity_tag(severity: str) -> str:
+    return paint(f'[{severity}]', SEVERITY_COLORS[severity])
+
+
+def did_you_mean(suggestions: List[str]) -> List[str]:
+    if len(suggestions) == 0:
+        return []
+    return [' -> did you mean'] + [f'   -> {x}' for x in suggestions]
+
+
+# a mod that would satisfy a dependency, but isn't the one loaded
+@define
+class CandidateReport:
+    modid:      str
+    version:    str
+    # the jar in the mods folder containing it
+    file:       str
+    # otherwise it's nested in `file`, and a top-level jar is loaded instead
+    disabled:   bool
+
+    def text(self) -> str:
+        if self.disabled:
+            return (
+                f"{self.modid} {self.version} is present but disabled "
+                f"({self.file})"
+            )
+        return (
+            f"{self.modid} {self.version} is nested in '{self.file}', but "
+            f"another jar is loaded instead"
+        )
+
+
+@define
+class DependencyReport:
+    modid:              str
+    required:           bool
+    versions:           List[str]
+    installed:          bool
+    installed_version:  Optional[str]
+    satisfied:          bool
+    # the jar in the mods folder providing the dependency, if installed
+    provider:           Optional[str] = None
+    # the modid of the installed mod, when it `provides` this one
+    satisfied_by:       Optional[str] = None
+    # an inactive mod that would satisfy it, when nothing active does
+    candidate:          Optional[CandidateReport] = None
+
+    def status(self) -> str:
+        if self.satisfied:
+            if not self.installed:
+                return 'not installed'
+            return f'installed {self.installed_version}'
+
+        if not self.installed:
+            status = 'missing'
+        else:
+            status = f'wrong version {self.installed_version}'
+        if self.candidate is not None:
+            status += f', {self.candidate.text()}'
+        return status
+
+    def text(self) -> str:
+        required = 'required' if self.required else 'optional'
+        text = (
+            f'{self.modid} [{", ".join(self.versions)}] ({required}): '
+            f'{self.status()}'
+        )
+        if self.satisfied_by is not None:
+            text += (
+                f' satisfied by {self.satisfied_by} (provides {self.modid})'
+            )
+        return text
+
+
+@define
+class ModReport:
+    modid:          str
+    name:           str
+    version:        str
+    loader:         Optional[str]
+    file:           str
+    lie_depends:    bool
+    dependencies:   List[DependencyReport] = Factory(list)
+    # the outer jar, for mods nested in another jar
+    provided_by:    Optional[str] = None
+    java_version:   Optional[int] = None
+    disabled:       bool = False
+    # other modids the mod satisfies dependencies on
+    provides:       List[str] = Factory(list)
+    # `client`, `server` or `*`
+    environment:    str = '*'
+
+
+@define
+class DependencyTreeReport:
+    dependency:     DependencyReport
+    dependencies:   List['DependencyTreeReport'] = Factory(list)
+    # the dependencies of this mod were already shown further up
+    repeated:       bool = False
+
+    def lines(self, depth: int) -> List[str]:
+        color = 'green' if self.dependency.satisfied else 'red'
+        repeated = ' (see above)' if self.repeated else ''
+        lines = [
+            f'{" " + "  " * depth}-> {paint(self.dependency.text(), color)}'
+            f'{repeated}'
+        ]
+        for dependency in self.dependencies:
+            lines.extend(dependency.lines(depth + 1))
+        return lines
+
+
+@define
+class DuplicateFileReport:
+    file:       str
+    version:    str
+    disabled:   bool
+
+
+@define
+class DuplicateReport:
+    modid:      str
+    files:      List[DuplicateFileReport]
+
+    def text(self) -> str:
+        lines: List[str] = [f"Duplicate mod '{self.modid}':"]
+        for file in self.files:
+            disabled = ' [disabled]' if file.disabled else ''
+            lines.append(
+                f' ->  [file]: {file.file} ({file.version}){disabled}'
+            )
+        return '\n'.join(lines)
+
+
+@define
+class ModInfoReport:
+    mods:       List[ModReport]
+    # set when a specific modid was requested
+    modid:      Optional[str] = None
+    duplicate:  Optional[DuplicateReport] = None
+    # similar modids, when the requested one wasn't found
+    suggestions: List[str] = Factory(list)
+    # with `--deps`, the transitive dependencies of the first mod
+    tree:       List[DependencyTreeReport] = Factory(list)
+
+    def table(self) -> str:
+        rows: List[List[str]] = [['MODID', 'VERSION', 'LOADER', 'FILE']]
+        for mod in self.mods:
+            flags = ''.join([
+                ' [lie-depends]' if mod.lie_depends else '',
+                ' [disabled]' if mod.disabled else '',
+            ])
+            rows.append([
+                mod.modid,
+                mod.version,
+                mod.loader or '-',
+                f'"{mod.file}"{flags}',
+            ])
+
+        widths = [max([len(x[i]) for x in rows]) for i in range(3)]
+        return '\n'.join([
+            '  '.join([x.ljust(y) for x, y in zip(row, widths)] + [row[3]])
+            for row in rows
+        ])
+
+    def text(self) -> str:
+        lines: List[str] = []
+
+        if self.modid is None:
+            return self.table()
+
+        if len(self.mods) == 0:
+            lines.append('==================================')
+            lines.append(f'mod-info: modid "{self.modid}" not found!')
+            lines.extend(did_you_mean(self.suggestions))
+            lines.append('')
+            return '\n'.join(lines)
+
+        for mod in self.mods:
+            lines.append(f'{mod.name} ({mod.modid}) [{mod.version}]:')
+            lines.append(f' -> File:        "{mod.file}"')
+            if mod.provided_by is not None:
+                lines.append(f' -> Provided by: "{mod.provided_by}"')
+            java = 'unknown'
+            if mod.java_version is not None:
+                java = f'{mod.java_version}+'
+            lines.append(f' -> Java:        {java}')
+            lines.append(f' -> Loader:      {mod.loader or "unknown"}')
+            environment = 'both' if mod.environment == '*' else mod.environment
+            lines.append(f' -> Side:        {environment}')
+            disabled = 'yes' if mod.disabled else 'no'
+            lines.append(f' -> Disabled:    {disabled}')
+            if len(mod.provides) > 0:
+                lines.append(f' -> Provides:    {", ".join(mod.provides)}')
+            lie = 'yes' if mod.lie_depends else 'no'
+            lines.append(f' -> Lie-depends: {lie}')
+            lines.append(' -> Dependencies')
+            for dep in mod.dependencies:
+                color = 'green' if dep.satisfied else 'red'
+                lines.append(f'   -> {paint(dep.text(), color)}')
+            lines.append('')
+
+        if len(self.tree) > 0:
+            lines.append(' -> Dependency tree')
+            for dependency in self.tree:
+                lines.extend(dependency.lines(1))
+            lines.append('')
+
+        if self.duplicate is not None:
+            lines.append(self.duplicate.text())
+            lines.append('')
+        return '\n'.join(lines)
+
+
+@define
+class SuggestionReport:
+    modid:      str
+    # False when modrinth couldn't be reached
+    checked:    bool = False
+    project:    Optional[str] = None
+    version:    Optional[str] = None
+    url:        Optional[str] = None
+    error:      Optional[str] = None
+
+    def text(self) -> str:
+        if not self.checked:
+            return f"couldn't check online ({self.error})"
+        if self.project is None:
+            return f"no modrinth project named '{self.modid}'"
+        if self.url is None:
+            return (
+                f"no compatible version of '{self.project}' found on "
+                f"modrinth"
+            )
+        return f'download {self.project} {self.version}: {self.url}'
+
+
+@define
+class UnsatisfiedEdgeReport:
+    dependent:          str
+    modid:              str
+    versions:           List[str]
+    installed:          bool
+    # a disabled jar that would satisfy the dependency
+    disabled_provider:  Optional[str] = None
+    # set with `--online`
+    suggestion:         Optional[SuggestionReport] = None
+
+
+@define
+class ChainReport:
+    chain:          List[str]
+    unsatisfied:    List[UnsatisfiedEdgeReport] = Factory(list)
+
+
+@define
+class WhyDependsReport:
+    modid:          str
+    installed:      bool
+    name:           Optional[str] = None
+    version:        Optional[str] = None
+    file:           Optional[str] = None
+    # the outer jar, for mods nested in another jar
+    provided_by:    Optional[str] = None
+    # the modid of the installed mod, when it `provides` this one
+    satisfied_by:   Optional[str] = None
+    # False when the mod isn't installed and nothing depends on it
+    found:          bool = True
+    errors_only:    bool = False
+    chains:         List[ChainReport] = Factory(list)
+    # only the first `graph.MAX_CHAINS` chains were followed
+    truncated:      bool = False
+    # similar modids, when the requested one wasn't found
+    suggestions:    List[str] = Factory(list)
+
+    def text(self) -> str:
+        lines: List[str] = []
+
+        if not self.found:
+            lines.append('==================================')
+            lines.append(
+                f'why-depends: modid "{self.modid}" is not installed and '
+                f'nothing depends on it!'
+            )
+            lines.extend(did_you_mean(self.suggestions))
+            lines.append('')
+            return '\n'.join(lines)
+
+        if self.installed:
+            modid = self.satisfied_by or self.modid
+            lines.append(f'{self.name} ({modid}) [{self.version}]:')
+            lines.append(f' -> File: "{self.file}"')
+            if self.provided_by is not None:
+                lines.append(f' -> Provided by: "{self.provided_by}"')
+            if self.satisfied_by is not None:
+                lines.append(
+                    f' -> Satisfied by: {self.satisfied_by} '
+                    f'(provides {self.modid})'
+                )
+            lines.append('')
+        else:
+            lines.append(f'{self.modid} [not installed]:\n')
+
+        if len(self.chains) == 0 and not self.errors_only:
+            lines.append(f' -> nothing depends on "{self.modid}"')
+            return '\n'.join(lines)
+
+        lines.append(' -> Dependency chains')
+        for chain in self.chains:
+            lines.append(f'   -> {" -> ".join(chain.chain)}')
+            for edge in chain.unsatisfied:
+                installed = 'installed' if edge.installed else 'not installed'
+                lines.append('     --> ' + paint(
+                    f'\'{edge.dependent}\' requires '
+                    f'\'{edge.modid}\' [{", ".join(edge.versions)}] '
+                    f'({installed})',
+                    'red'
+                ))
+                if edge.disabled_provider is not None:
+                    lines.append(
+                        f'       satisfied by disabled jar: '
+                        f'"{edge.disabled_provider}"'
+                    )
+                if edge.suggestion is not None:
+                    lines.append(
+                        f'       suggestion: {edge.suggestion.text()}'
+                    )
+
+        if self.truncated:
+            lines.append(
+                '   -> more chains were not followed, only the first '
+                'ones are listed'
+            )
+        elif self.errors_only and len(self.chains) == 0:
+            lines.append('   -> all dependency chains are satisfied')
+        lines.append('')
+        return '\n'.join(lines)
+
+
+@define
+class MentionedModReport:
+    modid:          str
+    installed:      bool
+    version:        Optional[str] = None
+    file:           Optional[str] = None
+    # similar installed modids, when it isn't installed
+    suggestions:    List[str] = Factory(list)
+
+
+@define
+class ErrorMatchReport:
+    name:           str
+    explanation:    str
+    fix:            str
+    example:        str
+    mods:           List[MentionedModReport] = Factory(list)
+
+
+@define
+class FindErrorReport:
+    error:          str
+    matches:        List[ErrorMatchReport] = Factory(list)
+    # closest known errors, when nothing matched
+    suggestions:    List[ErrorMatchReport] = Factory(list)
+
+    def text(self) -> str:
+        lines: List[str] = []
+
+        for match in self.matches:
+            lines.append('==================================')
+            lines.append(f'{match.name}:')
+            lines.append(f' -> explanation: {match.explanation}')
+            lines.append(f' -> fix:         {match.fix}')
+            if len(match.mods) > 0:
+                lines.append(' -> mentioned mods')
+            for mod in match.mods:
+                if mod.installed:
+                    lines.append(
+                        f'   -> {mod.modid}: installed [{mod.version}]'
+                    )
+                    lines.append(f'      file: "{mod.file}"')
+                else:
+                    lines.append(f'   -> {mod.modid}: not installed')
+                    if len(mod.suggestions) > 0:
+                        lines.append(
+                            f'      did you mean: {", ".join(mod.suggestions)}'
+                        )
+            lines.append('')
+
+        if len(self.matches) == 0:
+            lines.append('==================================')
+            lines.append(
+                'find-error: no known error matches the provided message'
+            )
+            lines.append(' -> closest known errors')
+            for suggestion in self.suggestions:
+                lines.append(
+                    f'   -> {suggestion.name}: "{suggestion.example}"'
+                )
+            lines.append('')
+
+        return '\n'.join(lines)
+
+
+@define
+class LogBlockReport:
+    # the first lines of the block, without repeated stack frames
+    lines:      List[str]
+    # how many times the block appears in the log
+    count:      int = 1
+    # lines left out of `lines`
+    hidden:     int = 0
+    matches:    List[ErrorMatchReport] = Factory(list)
+
+
+@define
+class CulpritReport:
+    modid:      str
+    version:    str
+    file:       str
+    score:      int = 0
+    # eg: `{'jar in stack frames': 2}`, counted once per error block
+    reasons:    Dict[str, int] = Factory(dict)
+
+
+@define
+class CrashLogReport:
+    file:       str
+    # error blocks found, before the ones shown were capped
+    total:      int = 0
+    blocks:     List[LogBlockReport] = Factory(list)
+    # installed mods behind the errors, most likely first
+    culprits:   List[CulpritReport] = Factory(list)
+
+    def text(self) -> str:
+        lines: List[str] = ['==================================']
+        plural = 's' if self.total != 1 else ''
+        lines.append(f'{self.file}: {self.total} error block{plural}')
+        if len(self.blocks) < self.total:
+            lines.append(f' -> showing {len(self.blocks)}')
+        lines.append('')
+
+        for block in self.blocks:
+            repeated = f' (x{block.count})' if block.count > 1 else ''
+            lines.append(paint(block.lines[0], 'red') + repeated)
+            lines.extend(block.lines[1:])
+            if block.hidden > 0:
+                lines.append(f'\t... {block.hidden} more lines')
+            for match in block.matches:
+                lines.append(f' -> {match.name}: {match.explanation}')
+                lines.append(f'    fix: {match.fix}')
+            lines.append('')
+
+        lines.append('Likely culprits:')
+        if len(self.culprits) == 0:
+            lines.append(' -> none of the installed mods are mentioned')
+        for culprit in self.culprits:
+            reasons = ', '.join([
+                f'{x} x{y}' if y > 1 else x for x, y in culprit.reasons.items()
+            ])
+            lines.append(
+                f' -> {culprit.modid} [{culprit.version}] '
+                f'(score {culprit.score}): {reasons}'
+            )
+            lines.append(f'    file: "{culprit.file}"')
+
+        return '\n'.join(lines)
+
+
+@define
+class ModErrorsReport:
+    modid:          str
+    name:           str
+    version:        str
+    file:           str
+    errors:         List[str]
+    unsatisfied:    List[DependencyReport] = Factory(list)
+
+
+@define
+class CycleReport:
+    modids:     List[str]
+    # whether every dependency along the cycle is required
+    mandatory:  bool
+
+    @property
+    def severity(self) -> str:
+        return 'warning' if self.mandatory else 'info'
+
+    def text(self) -> str:
+        cycle = ' -> '.join(self.modids + self.modids[:1])
+        return f'Dependency cycle {severity_tag(self.severity)}: {cycle}'
+
+
+@define
+class AccessOverlapReport:
+    # eg: `net.minecraft.client.Minecraft.f_91074_`
+    target:         str
+    # eg: `public-f` or `accessible`, by modid
+    access:         Dict[str, str] = Factory(dict)
+    # one of the mods makes the member final while another needs it not to
+    # be
+    incompatible:   bool = False
+
+    @property
+    def severity(self) -> str:
+        return 'warning' if self.incompatible else 'info'
+
+    def text(self) -> str:
+        mods = ', '.join([f'{x} ({y})' for x, y in self.access.items()])
+        return (
+            f"Access overlap {severity_tag(self.severity)}: "
+            f"'{self.target}' is changed by {mods}"
+        )
+
+
+@define
+class InstallStepReport:
+    modid:          str
+    # the mods that require it
+    requested_by:   List[str] = Factory(list)
+    # other missing mods it requires, to be installed first
+    after:          List[str] = Factory(list)
+    # a disabled jar of it in the profile, which its dependencies are from
+    file:           Optional[str] = None
+
+    def text(self) -> str:
+        line = f"{self.modid}, required by {', '.join(self.requested_by)}"
+        if len(self.after) > 0:
+            line += f", after {', '.join(self.after)}"
+        if self.file is not None:
+            line += f' (disabled: {self.file})'
+        return line
+
+
+@define
+class InstallOrderReport:
+    steps:      List[InstallStepReport] = Factory(list)
+    # the ones without a jar in the profile, so their own dependencies are
+    # unknown
+    unknown:    List[InstallStepReport] = Factory(list)
+
+    def text(self) -> str:
+        lines: List[str] = ['Install order of the missing mods:']
+        for i, step in enumerate(self.steps):
+            lines.append(f' -> {i + 1}. {step.text()}')
+        if len(self.unknown) > 0:
+            lines.append(
+                ' -> then, in any order, as their own dependencies are '
+                'unknown without their jars'
+            )
+        for step in self.unknown:
+            lines.append(f'   -> {step.text()}')
+        return '\n'.join(lines)
+
+
+# never affects the severity, these are only suggestions
+@define
+class RecommendationReport:
+    modid:          str
+    # `recommends` or `suggests`, by the modid of the mods wanting it
+    requested_by:   Dict[str, str] = Factory(dict)
+
+    def text(self) -> str:
+        mods = ', '.join([f'{x} ({y})' for x, y in self.requested_by.items()])
+        return f"Recommended mod '{self.modid}' is not installed: {mods}"
+
+
+@define
+class OrphanReport:
+    modid:      str
+    file:       str
+    # marked as a library in its metadata, rather than just having no
+    # content of its own
+    declared:   bool
+
+    @property
+    def severity(self) -> str:
+        return 'info'
+
+    def text(self) -> str:
+        reason = (
+            'is marked as a library' if self.declared
+            else 'looks like a library without content or mixins of its own'
+        )
+        return (
+            f"Orphaned library {severity_tag(self.severity)}: "
+            f"'{self.modid}' {reason} and nothing depends on it, it can "
+            f"probably be removed "
+            f"({self.file})"
+        )
+
+
+@define
+class ConflictReport:
+    modid:              str
+    conflicts_with:     str
+    versions:           List[str]
+    installed_version:  str
+    # `breaks` and `incompatible` can't load together, `conflicts` and
+    # `discouraged` only warn
+    hard:               bool
+
+    @property
+    def severity(self) -> str:
+        return 'error' if self.hard else 'warning'
+
+    def text(self) -> str:
+        verb = 'breaks' if self.hard else 'conflicts with'
+        return (
+            f"Conflict {severity_tag(self.severity)}: '{self.modid}' {verb} "
+            f"'{self.conflicts_with}' [{', '.join(self.versions)}] "
+            f"(installed: {self.installed_version})"
+        )
+
+
+# from the `incompat.toml` database rather than the mods' metadata
+@define
+class IncompatibilityReport:
+    modid:              str
+    incompatible_with:  str
+    reason:             str
+    # the database the entry is from
+    source:             str
+    severity:           str = 'warning'
+
+    def text(self) -> str:
+        return (
+            f"Known incompatibility {severity_tag(self.severity)}: "
+            f"'{self.modid}' and '{self.incompatible_with}': {self.reason} "
+            f"({self.source})"
+        )
+
+
+@define
+class MixinOverlapReport:
+    # eg: `net.minecraft.client.Minecraft`
+    target:     str
+    mods:       List[str] = Factory(list)
+    # methods replaced with `@Overwrite`, by modid
+    overwrites: Dict[str, List[str]] = Factory(dict)
+
+    @property
+    def severity(self) -> str:
+        return 'warning' if len(self.overwrites) > 0 else 'info'
+
+    def text(self) -> str:
+        lines: List[str] = [
+            f"Mixin overlap {severity_tag(self.severity)}: '{self.target}' "
+            f"is changed by {', '.join(self.mods)}"
+        ]
+        for modid, methods in self.overwrites.items():
+            lines.append(f" -> '{modid}' overwrites {', '.join(methods)}")
+        return '\n'.join(lines)
+
+
+@define
+class UnsatisfiedCountReport:
+    modid:          str
+    unsatisfied:    int
+
+
+@define
+class SummaryReport:
+    mods:           int
+    # eg: `{'forge': 40, 'fabric': 2}`
+    loaders:        Dict[str, int] = Factory(dict)
+    errors:         int = 0
+    warnings:       int = 0
+    # the mods with the most unsatisfied dependencies, most first
+    top:            List[UnsatisfiedCountReport] = Factory(list)
+
+    def text(self) -> str:
+        loaders = ', '.join([f'{x}: {y}' for x, y in self.loaders.items()])
+        mods = f'{self.mods} ({loaders})' if loaders else str(self.mods)
+        lines: List[str] = [
+            'Summary:',
+            f' -> Mods:     {mods}',
+            f' -> Errors:   {self.errors}',
+            f' -> Warnings: {self.warnings}',
+        ]
+        if len(self.top) > 0:
+            lines.append(' -> Most unsatisfied dependencies')
+            for mod in self.top:
+                lines.append(f'   -> {mod.modid}: {mod.unsatisfied}')
+        return '\n'.join(lines)
+
+
+@define
+class ValidateReport:
+    passed:     bool
+    mods:       List[ModErrorsReport] = Factory(list)
+    # errors that don't belong to a loaded mod, eg: unreadable jars
+    errors:     List[str] = Factory(list)
+    # the same, but not keeping the pack from loading
+    warnings:   List[str] = Factory(list)
+    duplicates: List[DuplicateReport] = Factory(list)
+    cycles:     List[CycleReport] = Factory(list)
+    conflicts:  List[ConflictReport] = Factory(list)
+    incompatibilities: List[IncompatibilityReport] = Factory(list)
+    # only checked with `--mixins`
+    mixin_overlaps: List[MixinOverlapReport] = Factory(list)
+    # only checked with `--access`
+    access_overlaps: List[AccessOverlapReport] = Factory(list)
+    # only checked with `--report-orphans`
+    orphans:    List[OrphanReport] = Factory(list)
+    # only checked with `--show-recommendations`
+    recommendations: List[RecommendationReport] = Factory(list)
+    # only with `--suggest-install-order`
+    install_order: Optional[InstallOrderReport] = None
+    # the most severe problem found, `None` when there are none
+    severity:   Optional[str] = None
+    summary:    Optional[SummaryReport] = None
+
+    def hard_conflicts(self) -> List[ConflictReport]:
+        return [x for x in self.conflicts if x.hard]
+
+    # the reports that have a severity of their own
+    def graded(self) -> List[Any]:
+        return [
+            *self.conflicts, *self.incompatibilities, *self.cycles,
+            *self.mixin_overlaps, *self.access_overlaps, *self.orphans
+        ]
+
+    def find_severity(self) -> Optional[str]:
+        if not self.passed:
+            return 'error'
+        found = [SEVERITIES.index(x.severity) for x in self.graded()]
+        if len(self.warnings) > 0:
+            found.append(SEVERITIES.index('warning'))
+        return SEVERITIES[max(found)] if len(found) > 0 else None
+
+    # `loaders` counts the mods of each loader
+    def summarize(self, loaders: Dict[str, int]) -> SummaryReport:
+        summary = SummaryReport(sum(loaders.values()), loaders)
+
+        severities = [x.severity for x in self.graded()]
+        summary.errors = (
+            severities.count('error')
+            + len(self.errors)
+            + len(self.duplicates)
+            + sum([len(x.errors) for x in self.mods])
+        )
+        summary.warnings = severities.count('warning') + len(self.warnings)
+
+        unsatisfied = sorted(
+            [x for x in self.mods if len(x.unsatisfied) > 0],
+            key=lambda x: (-len(x.unsatisfied), x.modid)
+        )
+        summary.top = [
+            UnsatisfiedCountReport(x.modid, len(x.unsatisfied))
+            for x in unsatisfied[:SUMMARY_TOP]
+        ]
+        return summary
+
+    # 0 when clean, 1 with warnings and 2 with errors
+    def exit_code(self, error_on_warning: bool = False) -> int:
+        if self.severity == 'error':
+            return 2
+        if self.severity == 'warning':
+            return 2 if error_on_warning else 1
+        return 0
+
+    def text(self) -> str:
+        lines: List[str] = []
+
+        for conflict in self.conflicts:
+            lines.append(conflict.text())
+        if len(self.conflicts) > 0:
+            lines.append('')
+
+        for incompatibility in self.incompatibilities:
+            lines.append(incompatibility.text())
+        if len(self.incompatibilities) > 0:
+            lines.append('')
+
+        for overlap in self.mixin_overlaps:
+            lines.append(overlap.text())
+        if len(self.mixin_overlaps) > 0:
+            lines.append('')
+
+        for access in self.access_overlaps:
+            lines.append(access.text())
+        if len(self.access_overlaps) > 0:
+            lines.append('')
+
+        for cycle in self.cycles:
+            lines.append(cycle.text())
+        if len(self.cycles) > 0:
+            lines.append('')
+
+        for orphan in self.orphans:
+            lines.append(orphan.text())
+        if len(self.orphans) > 0:
+            lines.append('')
+
+        for recommendation in self.recommendations:
+            lines.append(recommendation.text())
+        if len(self.recommendations) > 0:
+            lines.append('')
+
+        if self.install_order is not None and (
+                    len(self.install_order.steps) > 0
+                    or len(self.install_order.unknown) > 0
+                ):
+            lines.append(self.install_order.text())
+            lines.append('')
+
+        for duplicate in self.duplicates:
+            lines.append(duplicate.text())
+            lines.append('')
+
+        # a wrong version is listed under the dependency, and missing ones
+        # under the mod, so mods that only have the former are left out
+        for mod in [x for x in self.mods if len(x.errors) > 0]:
+            lines.append(f'{mod.name} ({mod.modid}) {mod.version}:')
+            lines.append(f' ->  [file]: {mod.file}')
+            for error in mod.errors:
+                lines.append(f' --> {paint(error, "red")}')
+            lines.append('')
+
+        for error in self.errors:
+            lines.append(f' -> {paint(error, "red")}')
+        for warning in self.warnings:
+            lines.append(f' -> {severity_tag("warning")} {warning}')
+
+        if self.passed:
+            lines.append(f' -> {paint("[PASS]", "green")}')
+
+        if self.summary is not None:
+            lines.append('')
+            lines.append(self.summary.text())
+
+        return '\n'.join(lines)
+
+
+@define
+class LoadOrderReport:
+    # empty when the ordering constraints contradict each other
+    order:      List[str] = Factory(list)
+    # provided by `--override-versions`, loaded before everything else
+    roots:      List[str] = Factory(list)
+    # cycles of mods that each have to load before the next
+    conflicts:  List[List[str]] = Factory(list)
+
+    def text(self) -> str:
+        lines: List[str] = []
+
+        if len(self.conflicts) > 0:
+            lines.append('load-order: contradicting ordering constraints')
+            for conflict in self.conflicts:
+                cycle = ' -> '.join(conflict + conflict[:1])
+                lines.append(f' -> {cycle}')
+            lines.append(' -> each mod has to load before the next one')
+            return '\n'.join(lines)
+
+        width = len(str(len(self.order)))
+        for i, modid in enumerate(self.order):
+            root = ' [provided]' if modid in self.roots else ''
+            lines.append(f'{str(i + 1).rjust(width)}. {modid}{root}')
+        return '\n'.join(lines)
+
+
+@define
+class CurseForgeReport:
+    manifest:           str
+    valid:              bool = False
+    name:               Optional[str] = None
+    version:            Optional[str] = None
+    minecraft_version:  Optional[str] = None
+    loader:             Optional[str] = None
+    files:              int = 0
+    optional_files:     int = 0
+    errors:             List[str] = Factory(list)
+
+    def text(self) -> str:
+        lines: List[str] = [f'CurseForge manifest "{self.manifest}":']
+        if self.name is not None:
+            version = f' [{self.version}]' if self.version else ''
+            lines.append(f' -> Pack:      {self.name}{version}')
+        lines.append(f' -> Minecraft: {self.minecraft_version or "unknown"}')
+        lines.append(f' -> Loader:    {self.loader or "unknown"}')
+        lines.append(
+            f' -> Mod files: {self.files} ({self.optional_files} optional)'
+        )
+        for error in self.errors:
+            lines.append(f' --> {error}')
+        if self.valid:
+            lines.append(f' -> {paint("[PASS]", "green")}')
+        lines.append('')
+        return '\n'.join(lines)
+
+
+@define
+class ExportReport:
+    output:         str
+    # eg: `{'minecraft': '1.20.1', 'forge': '47.2.0'}`
+    dependencies:   Dict[str, str]
+    # files downloaded from their url by the launcher
+    files:          List[str] = Factory(list)
+    # files without a usable url, shipped inside the pack
+    overrides:      List[str] = Factory(list)
+
+    def text(self) -> str:
+        lines: List[str] = [f'export: wrote "{self.output}"']
+        for name, version in self.dependencies.items():
+            lines.append(f' -> {name}: {version}')
+        lines.append(f' -> {len(self.files)} downloaded files')
+        for path in self.files:
+            lines.append(f'   -> {path}')
+        lines.append(f' -> {len(self.overrides)} files in overrides')
+        for path in self.overrides:
+            lines.append(f'   -> {path}')
+        return '\n'.join(lines)
+
+
+@define
+class ClientDependencyReport:
+    modid:      str
+    # the client-only mod it requires, which had to be kept
+    requires:   str
+
+    def text(self) -> str:
+        return (
+            f"{severity_tag('error')} '{self.modid}' runs on servers but "
+            f"requires the client-only '{self.requires}', kept it"
+        )
+
+
+@define
+class ServerExportReport:
+    output:         str
+    # symlinked instead of copied
+    linked:         bool
+    files:          List[str] = Factory(list)
+    # client-only jars left out
+    excluded:       List[str] = Factory(list)
+    client_dependencies: List[ClientDependencyReport] = Factory(list)
+
+    def text(self) -> str:
+        verb = 'linked' if self.linked else 'copied'
+        lines: List[str] = [
+            f'export-server: {verb} {len(self.files)} jars to '
+            f'"{self.output}"'
+        ]
+        lines.append(f' -> {len(self.excluded)} client-only jars excluded')
+        for name in self.excluded:
+            lines.append(f'   -> {name}')
+        for dependency in self.client_dependencies:
+            lines.append(f' -> {dependency.text()}')
+        return '\n'.join(lines)
+
+
+@define
+class GraphReport:
+    output:     str
+    nodes:      int = 0
+    edges:      int = 0
+
+    def text(self) -> str:
+        return (
+            f'graph: wrote {self.nodes} nodes and {self.edges} edges to '
+            f'"{self.output}"'
+        )
+
+
+@define
+class CleanReport:
+    dry_run:    bool
+    # orphaned config files and directories
+    removed:    List[str] = Factory(list)
+    # entries that couldn't be associated with a modid either way
+    skipped:    List[str] = Factory(list)
+
+    def text(self) -> str:
+        lines: List[str] = []
+
+        action = 'would remove' if self.dry_run else 'removed'
+        lines.append(f'clean: {action} {len(self.removed)} config entries')
+        for name in self.removed:
+            lines.append(f' -> {name}')
+        if len(self.skipped) > 0:
+            lines.append(f'clean: skipped {len(self.skipped)} unrecognized')
+            for name in self.skipped:
+                lines.append(f' -> {name}')
+        if self.dry_run and len(self.removed) > 0:
+            lines.append('clean: pass --confirm to delete them')
+
+        return '\n'.join(lines)
+
+
+@define
+class LockFileReport:
+    # the name of the jar in the mods folder
+    file:       str
+    hash:       str
+    # of the mod in the jar, if it has one
+    version:    Optional[str] = None
+
+
+@define
+class LockReport:
+    output:     str
+    files:      List[LockFileReport] = Factory(list)
+
+    def text(self) -> str:
+        lines: List[str] = [
+            f'lock: wrote {len(self.files)} files to "{self.output}"'
+        ]
+        for file in self.files:
+            version = f' [{file.version}]' if file.version else ''
+            lines.append(f' -> {file.file}{version}')
+        return '\n'.join(lines)
+
+
+@define
+class LockChangeReport:
+    file:           str
+    old_version:    Optional[str]
+    new_version:    Optional[str]
+
+
+@define
+class VerifyReport:
+    lock:       str
+    passed:     bool = True
+    added:      List[LockFileReport] = Factory(list)
+    removed:    List[LockFileReport] = Factory(list)
+    # files with a different hash than when they were locked
+    changed:    List[LockChangeReport] = Factory(list)
+    unchanged:  int = 0
+
+    def text(self) -> str:
+        lines: List[str] = [f'verify: "{self.lock}"']
+
+        for file in self.added:
+            lines.append(paint(f' + {file.file}', 'green'))
+        for file in self.removed:
+            lines.append(paint(f' - {file.file}', 'red'))
+        for change in self.changed:
+            versions = ''
+            if change.old_version != change.new_version:
+                versions = (
+                    f' [{change.old_version} -> {change.new_version}]'
+                )
+            lines.append(paint(f' ~ {change.file}{versions}', 'yellow'))
+
+        lines.append(f' -> {self.unchanged} files unchanged')
+        if self.passed:
+            lines.append(f' -> {paint("[PASS]", "green")}')
+        else:
+            lines.append(
+                f' -> {paint("[FAIL]", "red")} {len(self.added)} added, '
+                f'{len(self.removed)} removed, {len(self.changed)} changed'
+            )
+        return '\n'.join(lines)
+
+
+@define
+class DiffModReport:
+    modid:      str
+    version:    str
+    file:       str
+
+
+@define
+class VersionChangeReport:
+    modid:          str
+    old_version:    str
+    new_version:    str
+
+
+@define
+class DiffReport:
+    old:        str
+    new:        str
+    added:      List[DiffModReport] = Factory(list)
+    removed:    List[DiffModReport] = Factory(list)
+    changed:    List[VersionChangeReport] = Factory(list)
+
+    def text(self) -> str:
+        lines: List[str] = [f'diff: "{self.old}" -> "{self.new}"', '']
+
+        lines.append(f'Added ({len(self.added)}):')
+        for mod in self.added:
+            lines.append(paint(f' + {mod.modid} [{mod.version}]', 'green'))
+        lines.append('')
+
+        lines.append(f'Removed ({len(self.removed)}):')
+        for mod in self.removed:
+            lines.append(paint(f' - {mod.modid} [{mod.version}]', 'red'))
+        lines.append('')
+
+        lines.append(f'Changed ({len(self.changed)}):')
+        for change in self.changed:
+            lines.append(paint(
+                f' ~ {change.modid} [{change.old_version} -> '
+                f'{change.new_version}]',
+                'yellow'
+            ))
+        return '\n'.join(lines)
+
+
+@define
+class ProfileReport:
+    instance:   str
+    report:     Any
+
+
+@define
+class ProfilesReport:
+    profiles:   List[ProfileReport] = Factory(list)
+
+    def text(self) -> str:
+        lines: List[str] = []
+        for profile in self.profiles:
+            lines.append('==================================')
+            lines.append(f'profile: "{profile.instance}"')
+            lines.append(profile.report.text())
+        return '\n'.join(lines)
+
+
+def emit(format: str, value: Any) -> None:
+    if format == 'json':
+        print(json.dumps(asdict(value), indent=2))
+    else:
+        print(value.text())
@@ -0,0 +1,79 @@
+
+from typing import Dict, Set, Tuple
+
+from version import Version, BadVersionString
+
+
+class OverrideError(ValueError):
+    ...
+
+
+# characters that make a modid a glob pattern, see `fnmatch`
+PATTERN_CHARACTERS = '*?['
+
+
+def is_pattern(modid: str) -> bool:
+    return any([x in modid for x in PATTERN_CHARACTERS])
+
+
+# parses `<modid>=<version>`, where a version of `*` accepts any range
+def parse_override(token: str) -> Tuple[str, Version]:
+    if '=' not in token:
+        raise OverrideError(
+            f"invalid override '{token}': expected <modid>=<version>"
+        )
+
+    modid, version = [x.strip() for x in token.split('=', 1)]
+    if modid == '':
+        raise OverrideError(f"invalid override '{token}': missing modid")
+    if version == '':
+        raise OverrideError(f"invalid override '{token}': missing version")
+    if is_pattern(modid):
+        raise OverrideError(
+            f"invalid override '{token}': a modid can't be a pattern, "
+            f"only the version can be `*`"
+        )
+
+    try:
+        return modid, Version.fromString(version)
+    except BadVersionString:
+        raise OverrideError(
+            f"invalid override '{token}': could not parse version "
+            f"'{version}'"
+        )
+
+
+# parses `<modid>=<version>[,<modid>=<version>[,...]]`
+def parse_overrides(text: str) -> Dict[str, Version]:
+    overrides: Dict[str, Version] = {}
+
+    for token in text.split(','):
+        token = token.strip()
+        if token == '':
+            continue
+        modid, version = parse_override(token)
+        overrides[modid] = version
+
+    return overrides
+
+
+# parses a `<modid>=<version>` per line, ignoring `#` comments
+def parse_override_file(text: str, path: str) -> Dict[str, Version]:
+    overrides: Dict[str, Version] = {}
+
+    for i, line in enumerate(text.splitlines()):
+        line = line.split('#', 1)[0].strip()
+        if line == '':
+            continue
+        try:
+            modid, version = parse_override(line)
+        except OverrideError as e:
+            raise OverrideError(f'{path}:{i + 1}: {e}') from e
+        overrides[modid] = version
+
+    return overrides
+
+
+# parses `<modid>[,<modid>[,...]]`, where modids can be glob patterns
+def parse_lies(text: str) -> Set[str]:
+    return set([x.strip() for x in text.split(',') if x.strip() != ''])
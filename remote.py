@@ -0,0 +1,110 @@
+
+from typing import TYPE_CHECKING, Any, Dict, List, Optional
+from urllib.parse import quote, urlencode
+from urllib.error import URLError
+import urllib.request
+import json
+
+from output import SuggestionReport
+from version import Version, BadVersionString
+
+if TYPE_CHECKING:
+    from mod_info import ModDependency
+
+
+API_URL = 'https://api.modrinth.com/v2'
+USER_AGENT = 'MaxTheMooshroom/mc-packer'
+TIMEOUT = 10
+
+
+class RemoteError(Exception):
+    ...
+
+
+class ModrinthResolver:
+    minecraft_version:  Optional[str]
+    loader:             Optional[str]
+    # responses by url, kept for the rest of the run
+    _responses:         Dict[str, Any]
+
+    def __init__(
+                self,
+                minecraft_version: Optional[str],
+                loader: Optional[str]
+            ):
+        self.minecraft_version = minecraft_version
+        self.loader = loader
+        self._responses = {}
+
+    def get(
+                self,
+                path: str,
+                query: Optional[Dict[str, Any]] = None
+            ) -> Any:
+        url = f'{API_URL}{path}'
+        if query:
+            url += '?' + urlencode(
+                {key: json.dumps(value) for key, value in query.items()}
+            )
+        if url in self._responses:
+            return self._responses[url]
+
+        request = urllib.request.Request(
+            url,
+            headers={'User-Agent': USER_AGENT}
+        )
+        try:
+            with urllib.request.urlopen(request, timeout=TIMEOUT) as response:
+                data = json.loads(response.read().decode())
+        except URLError as e:
+            # a 404 is an answer too, only remember those
+            if getattr(e, 'code', None) == 404:
+                self._responses[url] = None
+                return None
+            raise RemoteError(str(e)) from e
+        except (OSError, ValueError) as e:
+            raise RemoteError(str(e)) from e
+
+        self._responses[url] = data
+        return data
+
+    # a version of the missing mod compatible with the profile
+    def suggest(self, dep: 'ModDependency') -> SuggestionReport:
+        report = SuggestionReport(dep.modid)
+
+        query: Dict[str, List[str]] = {}
+        if self.loader is not None:
+            query['loaders'] = [self.loader]
+        if self.minecraft_version is not None:
+            query['game_versions'] = [self.minecraft_version]
+
+        try:
+            # modids are usually the project slug as well
+            project = self.get(f'/project/{quote(dep.modid)}')
+            if project is None:
+                report.checked = True
+                return report
+            versions = self.get(f'/project/{project["id"]}/version', query)
+        except (RemoteError, KeyError, TypeError) as e:
+            report.error = str(e)
+            return report
+
+        report.checked = True
+        report.project = project.get('slug', dep.modid)
+        for version in versions or []:
+            try:
+                mod_version = Version.fromString(version['version_number'])
+            except (BadVersionString, KeyError):
+                continue
+            if not any([x.contains(mod_version) for x in dep.version_reqs]):
+                continue
+
+            files = version.get('files', [])
+            primary = [x for x in files if x.get('primary', False)] or files
+            if len(primary) == 0:
+                continue
+            report.version = version['version_number']
+            report.url = primary[0].get('url', None)
+            break
+
+        return report
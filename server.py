@@ -0,0 +1,92 @@
+
+from typing import Dict, List
+import shutil
+import os
+
+from filesystem import FileBase, DirectoryReal
+from mod_info import ModPack, Mod
+from mrpack import ExportError
+from output import ServerExportReport, ClientDependencyReport
+
+
+# the mods of each top-level jar in the mods folder, nested ones included
+def jar_mods(pack: ModPack) -> Dict[str, List[Mod]]:
+    jars: Dict[str, List[Mod]] = {}
+    for mod in sorted(pack.mods.values(), key=lambda x: x.modid):
+        if mod.info is not None:
+            jars.setdefault(mod.jar_file, []).append(mod)
+    return jars
+
+
+# a jar is client-only when what it is itself for is, nested libraries
+# don't count unless there is nothing else
+def is_client_jar(mods: List[Mod]) -> bool:
+    own = [x for x in mods if not x.nested] or mods
+    return all([x.environment == 'client' for x in own])
+
+
+# copies or links the jars a dedicated server runs into `output`, leaving
+# out client-only ones, unless a server mod requires them
+def export_server(
+            pack: ModPack,
+            output: str,
+            link: bool = False
+        ) -> ServerExportReport:
+    report = ServerExportReport(output, link)
+
+    mod_dir = DirectoryReal(pack.directory, 'mods')
+    jars = jar_mods(pack)
+    excluded = set([x for x, mods in jars.items() if is_client_jar(mods)])
+
+    # keeping a jar can make its requirements necessary in turn
+    changed = True
+    while changed:
+        changed = False
+        for jar, mods in sorted(jars.items()):
+            if jar in excluded:
+                continue
+            for mod in mods:
+                for dep in mod.dependencies:
+                    if not dep.required or dep.side.lower() == 'client':
+                        continue
+                    provider = pack.provider(dep.modid)
+                    if provider is None or provider.jar_file not in excluded:
+                        continue
+                    report.client_dependencies.append(
+                        ClientDependencyReport(mod.modid, provider.modid)
+                    )
+                    excluded.discard(provider.jar_file)
+                    changed = True
+
+    try:
+        os.makedirs(output, exist_ok=True)
+    except OSError as e:
+        raise ExportError(
+            f"export-server: failed to create '{output}': {e}"
+        ) from e
+
+    for item in sorted(mod_dir.list(), key=lambda x: x.name):
+        if not issubclass(type(item), FileBase):
+            continue
+        if not item.name.endswith('.jar'):
+            continue
+        path = os.path.join(mod_dir.full_path, item.name)
+        if path in excluded:
+            report.excluded.append(item.name)
+            continue
+
+        target = os.path.join(output, item.name)
+        try:
+            if os.path.lexists(target):
+                os.remove(target)
+            if link:
+                os.symlink(os.path.abspath(path), target)
+            else:
+                shutil.copy2(path, target)
+        except OSError as e:
+            raise ExportError(
+                f"export-server: failed to write '{target}': {e}"
+            ) from e
+        report.files.append(item.name)
+
+    return report
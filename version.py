@@ -1,13 +1,16 @@
 
 from attrs import define
 
-from typing import cast, List
+from typing import cast, List, Tuple
 import sys
 import re
 
 
 VERSION_DELIMITERS = ['+', '_', ':']
 
+# loaders whose metadata uses semver-style predicates instead of maven ranges
+FABRIC_LOADERS = ['fabric', 'quilt']
+
 
 class BadVersionString(ValueError):
     ...
@@ -20,6 +23,8 @@ class ValidationFailure(Exception):
 @define
 class VersionPart:
     components: List[int]
+    # parts like `beta.1` sort before the release they precede
+    prerelease: bool = False
 
     def __str__(self) -> str:
         return '.'.join([str(x) for x in self.components])
@@ -78,6 +83,17 @@ class VersionPart:
         return True
 
 
+# missing parts compare as `0`, unless the other version continues with a
+# pre-release part, in which case the shorter version is the release
+RELEASE_PART = VersionPart([sys.maxsize])
+
+
+def _missing_part(other: List[VersionPart], i: int) -> VersionPart:
+    if len(other) > i and other[i].prerelease:
+        return RELEASE_PART
+    return VersionPart([0])
+
+
 @define
 class Version:
     text: str
@@ -97,21 +113,26 @@ class Version:
         if text_raw in ["", "*"]:
             return cls("*")
 
+        # `!` marks pre-release qualifiers until the parts are split
         text = text_raw.lower()
-        text = text.replace('alpha', '0')
-        text = text.replace('beta', '1')
-        text = text.replace('pre-release', '2')
-        text = text.replace('pre', '2')
-        text = text.replace('rc', '2')
-        text = text.replace('snapshot', '2')
+        text = text.replace('alpha', '!0')
+        text = text.replace('beta', '!1')
+        text = text.replace('pre-release', '!2')
+        text = text.replace('pre', '!2')
+        text = text.replace('rc', '!2')
+        text = text.replace('snapshot', '!2')
         text = text.replace('release', '3')
 
         for DELIMITER in VERSION_DELIMITERS:
             text = text.replace(DELIMITER, '-')
 
         valid_parts = []
+        prerelease_parts = []
         part_candidates = text.split('-')
+        text = text.replace('!', '')
         for candidate in part_candidates:
+            prerelease = candidate.startswith('!')
+            candidate = candidate.replace('!', '')
             if candidate == '':
                 continue
 
@@ -136,13 +157,18 @@ class Version:
                     candidate = candidate.replace(f'{letter}', f'{idx}')
 
                 valid_parts.append(candidate)
+                prerelease_parts.append(prerelease)
 
         if len(valid_parts) > 0:
             return cls(
                 text, [
                     VersionPart(
-                        [int(x) for x in components.split('.') if x != '']
-                    ) for components in valid_parts
+                        [int(x) for x in components.split('.') if x != ''],
+                        prerelease
+                    ) for components, prerelease in zip(
+                        valid_parts,
+                        prerelease_parts
+                    )
                 ]
             )
 
@@ -150,8 +176,14 @@ class Version:
 
     def __eq__(self, other: 'Version') -> bool:  # type: ignore[override]
         for i in range(max(len(self.parts), len(other.parts))):
-            a = self.parts[i] if len(self.parts) > i else VersionPart([0])
-            b = other.parts[i] if len(other.parts) > i else VersionPart([0])
+            a = (
+                self.parts[i] if len(self.parts) > i
+                else _missing_part(other.parts, i)
+            )
+            b = (
+                other.parts[i] if len(other.parts) > i
+                else _missing_part(self.parts, i)
+            )
             if a < b:
                 return False
             elif a > b:
@@ -160,8 +192,14 @@ class Version:
 
     def __lt__(self, other: 'Version') -> bool:
         for i in range(max(len(self.parts), len(other.parts))):
-            a = self.parts[i] if len(self.parts) > i else VersionPart([0])
-            b = other.parts[i] if len(other.parts) > i else VersionPart([0])
+            a = (
+                self.parts[i] if len(self.parts) > i
+                else _missing_part(other.parts, i)
+            )
+            b = (
+                other.parts[i] if len(other.parts) > i
+                else _missing_part(self.parts, i)
+            )
             if a < b:
                 return True
             elif a > b:
@@ -170,8 +208,14 @@ class Version:
 
     def __le__(self, other: 'Version') -> bool:
         for i in range(max(len(self.parts), len(other.parts))):
-            a = self.parts[i] if len(self.parts) > i else VersionPart([0])
-            b = other.parts[i] if len(other.parts) > i else VersionPart([0])
+            a = (
+                self.parts[i] if len(self.parts) > i
+                else _missing_part(other.parts, i)
+            )
+            b = (
+                other.parts[i] if len(other.parts) > i
+                else _missing_part(self.parts, i)
+            )
             if a < b:
                 return True
             elif a > b:
@@ -180,8 +224,14 @@ class Version:
 
     def __gt__(self, other: 'Version') -> bool:
         for i in range(max(len(self.parts), len(other.parts))):
-            a = self.parts[i] if len(self.parts) > i else VersionPart([0])
-            b = other.parts[i] if len(other.parts) > i else VersionPart([0])
+            a = (
+                self.parts[i] if len(self.parts) > i
+                else _missing_part(other.parts, i)
+            )
+            b = (
+                other.parts[i] if len(other.parts) > i
+                else _missing_part(self.parts, i)
+            )
             if a > b:
                 return True
             elif a < b:
@@ -190,8 +240,14 @@ class Version:
 
     def __ge__(self, other: 'Version') -> bool:
         for i in range(max(len(self.parts), len(other.parts))):
-            a = self.parts[i] if len(self.parts) > i else VersionPart([0])
-            b = other.parts[i] if len(other.parts) > i else VersionPart([0])
+            a = (
+                self.parts[i] if len(self.parts) > i
+                else _missing_part(other.parts, i)
+            )
+            b = (
+                other.parts[i] if len(other.parts) > i
+                else _missing_part(self.parts, i)
+            )
             if a > b:
                 return True
             elif a < b:
@@ -258,7 +314,7 @@ class VersionRange:
 
         found = False
         for range in re.findall(
-                    r'([\[\(][0-9a-zA-Z+-_:., ]*[\]\)])',
+                    r'([\[\(][^\[\]\(\)]*[\]\)])',
                     range_raw
                 ):
             found = True
@@ -288,6 +344,114 @@ class VersionRange:
 
         return ranges
 
+    @classmethod
+    def fromFabricString(cls, range_raw: str) -> List['VersionRange']:
+        ranges: List['VersionRange'] = []
+
+        # alternatives are separated by `||`, and the space-separated
+        # predicates within each alternative must all be met
+        for alternative in range_raw.split('||'):
+            any_version = Version.fromString("*")
+            lower = VersionRangePart(any_version, True)
+            upper = VersionRangePart(any_version, True)
+
+            for predicate in alternative.split():
+                pred_lower, pred_upper = cls._fabricPredicate(predicate)
+                lower = cls._tighterBound(lower, pred_lower, True)
+                upper = cls._tighterBound(upper, pred_upper, False)
+
+            ranges.append(cls(lower, upper))
+
+        return ranges
+
+    @classmethod
+    def fromLoaderString(
+                cls,
+                range_raw: str,
+                loader: str
+            ) -> List['VersionRange']:
+        if loader in FABRIC_LOADERS:
+            return cls.fromFabricString(range_raw)
+        return cls.fromString(range_raw)
+
+    @staticmethod
+    def _bump(version: Version, index: int) -> Version:
+        components = list(version.parts[0].components)
+        while len(components) <= index:
+            components.append(0)
+        components = components[:index] + [components[index] + 1]
+        return Version.fromString('.'.join([str(x) for x in components]))
+
+    @staticmethod
+    def _tighterBound(
+                a: VersionRangePart,
+                b: VersionRangePart,
+                lower: bool
+            ) -> VersionRangePart:
+        if a.bound.text == "*":
+            return b
+        if b.bound.text == "*":
+            return a
+        if a.bound == b.bound:
+            return a if not a.inclusive else b
+        if lower:
+            return a if a.bound > b.bound else b
+        return a if a.bound < b.bound else b
+
+    @classmethod
+    def _fabricPredicate(
+                cls,
+                predicate: str
+            ) -> Tuple[VersionRangePart, VersionRangePart]:
+        any_part = VersionRangePart(Version.fromString("*"), True)
+
+        match = re.fullmatch(r'(>=|<=|>|<|=|~|\^)?(.+)', predicate)
+        if not match:
+            raise BadVersionString(f"Could not form from '{predicate}'")
+        operator, text = match.groups()
+
+        if text in ['*', 'x', 'X']:
+            return any_part, any_part
+
+        # `1.20.x` means any version starting with `1.20`
+        wildcard = re.fullmatch(r'(.+)\.[xX*]', text)
+        if wildcard:
+            base = Version.fromString(wildcard.groups()[0])
+            index = len(base.parts[0].components) - 1
+            return (
+                VersionRangePart(base, True),
+                VersionRangePart(cls._bump(base, index), False)
+            )
+
+        version = Version.fromString(text)
+        if version.text == "*":
+            return any_part, any_part
+
+        if operator in [None, '=']:
+            part = VersionRangePart(version, True)
+            return part, part
+        elif operator == '>=':
+            return VersionRangePart(version, True), any_part
+        elif operator == '>':
+            return VersionRangePart(version, False), any_part
+        elif operator == '<=':
+            return any_part, VersionRangePart(version, True)
+        elif operator == '<':
+            return any_part, VersionRangePart(version, False)
+        elif operator == '~':
+            # `~1.2.3` allows patch updates, `~1` allows minor updates
+            index = 1 if len(version.parts[0].components) > 1 else 0
+            return (
+                VersionRangePart(version, True),
+                VersionRangePart(cls._bump(version, index), False)
+            )
+        else:
+            # `^1.2.3` allows anything up to the next major version
+            return (
+                VersionRangePart(version, True),
+                VersionRangePart(cls._bump(version, 0), False)
+            )
+
 
 def test():
     a = Version.fromString('1.20.2+forge+0.1'       )
@@ -342,6 +506,42 @@ def test():
     print(a)
     print('[PASS]' if not bc.contains(a) else '[FAIL]')
     print('===========================================================')
+    for loader, range_raw, version_raw, expected in [
+        ('forge',   '[,)',                  '1.0',              True ),
+        ('forge',   '[1.2.3]',              '1.2.3',            True ),
+        ('forge',   '[1.2.3]',              '1.2.4',            False),
+        ('forge',   '[1.20.1,1.21)',        '1.20.6',           True ),
+        ('forge',   '[1.20.1,1.21)',        '1.21',             False),
+        ('forge',   '(1.0,2.0]',            '1.0',              False),
+        ('forge',   '[47.1.0,)',            '48.0.1',           True ),
+        ('forge',   '(,1.0],[1.2,)',        '1.1',              False),
+        ('forge',   '(,1.0],[1.2,)',        '1.3',              True ),
+        ('forge',   '[1.0,2.0),[3.0,)',     '3.1',              True ),
+        ('forge',   '[1.0,)',               '1.0-beta.2',       False),
+        ('forge',   '[1.0-beta.1,)',        '1.0-beta.2',       True ),
+        ('forge',   '[1.0-rc.1,)',          '1.0',              True ),
+        ('forge',   '[1.0,2.0)',            '2.0-rc.1',         True ),
+        ('fabric',  '*',                    '0.1',              True ),
+        ('fabric',  '>=1.2.3',              '1.2.3',            True ),
+        ('fabric',  '>=1.2.3',              '1.2.2',            False),
+        ('fabric',  '>=1.20 <1.21',         '1.20.4',           True ),
+        ('fabric',  '>=1.20 <1.21',         '1.21',             False),
+        ('fabric',  '~1.2',                 '1.2.9',            True ),
+        ('fabric',  '~1.2',                 '1.3',              False),
+        ('fabric',  '^1.2.3',               '1.9',              True ),
+        ('fabric',  '^1.2.3',               '2.0',              False),
+        ('fabric',  '1.20.x',               '1.20.1',           True ),
+        ('fabric',  '1.20.x',               '1.21',             False),
+        ('fabric',  '1.19.2 || >=1.20',     '1.19.2',           True ),
+        ('fabric',  '1.19.2 || >=1.20',     '1.19.4',           False),
+    ]:
+        version = Version.fromString(version_raw)
+        ranges = VersionRange.fromLoaderString(range_raw, loader)
+        result = any([range.contains(version) for range in ranges])
+        print(f'{loader} {range_raw}')
+        print(version)
+        print('[PASS]' if result == expected else '[FAIL]')
+        print('===========================================================')
 
 
 if __name__ == '__main__':
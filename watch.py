@@ -0,0 +1,66 @@
+
+from watchdog.observers import Observer
+from watchdog.events import FileSystemEventHandler, FileSystemEvent
+
+from typing import Callable
+import threading
+import os
+
+
+# launchers write temporary files before renaming them, so wait for the
+# events to stop for this long before validating again
+DEBOUNCE = 1.0
+# how often the main thread wakes up, so ctrl-c isn't stuck in a wait
+POLL_INTERVAL = 0.5
+
+
+def is_mod_file(path: str) -> bool:
+    return path.endswith('.jar') or path.endswith('.jar.disabled')
+
+
+class ModChangeHandler(FileSystemEventHandler):
+    changed: threading.Event
+
+    def __init__(self):
+        super().__init__()
+        self.changed = threading.Event()
+
+    def on_any_event(self, event: FileSystemEvent) -> None:
+        if event.is_directory:
+            return
+        # renames have both, eg: `mod.jar.part` -> `mod.jar`
+        paths = [event.src_path, getattr(event, 'dest_path', '')]
+        if any([is_mod_file(str(x)) for x in paths]):
+            self.changed.set()
+
+
+# runs `validate` once, then whenever jars in `mods/` are added, removed or
+# renamed, until ctrl-c
+def watch(
+            instance: str,
+            validate: Callable[[], None],
+            debounce: float = DEBOUNCE
+        ) -> None:
+    handler = ModChangeHandler()
+    observer = Observer()
+    observer.schedule(
+        handler,
+        os.path.join(instance, 'mods'),
+        recursive=False
+    )
+    observer.start()
+
+    try:
+        validate()
+        while True:
+            if not handler.changed.wait(POLL_INTERVAL):
+                continue
+            while handler.changed.is_set():
+                handler.changed.clear()
+                handler.changed.wait(debounce)
+            validate()
+    except KeyboardInterrupt:
+        pass
+    finally:
+        observer.stop()
+        observer.join()